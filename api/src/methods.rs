@@ -2,15 +2,16 @@ use lazy_static::lazy_static;
 
 use core::mem;
 
+use std::env;
+use std::fs;
 use std::thread;
 use std::time::Duration;
 use std::sync::Mutex;
 
 use rand::{Rng};
-use rand::prelude::ThreadRng;
 
 use actix_web::*;
-use actix_web::web::{Json, Path};
+use actix_web::web::{Json, Path, Query};
 
 use serde::{Deserialize};
 
@@ -32,11 +33,33 @@ fn build_new_world() -> World {
     let height = 40;
     let mut world = World::new(width, height, settings);
 
+    if let Ok(path) = env::var("MORPHOID_MAP") {
+        if let Err(error) = world.load_map(&path) {
+            println!("BUILD_NEW_WORLD: could not load map {:?}: {:?}", path, error);
+        }
+    }
+
+    if let Ok(path) = env::var("MORPHOID_REGIONS") {
+        if let Err(error) = load_regions(&mut world, &path) {
+            println!("BUILD_NEW_WORLD: could not load regions {:?}: {:?}", path, error);
+        }
+    }
+
+    if let Ok(path) = env::var("MORPHOID_SCHEDULE") {
+        if let Err(error) = load_schedule(&mut world, &path) {
+            println!("BUILD_NEW_WORLD: could not load schedule {:?}: {:?}", path, error);
+        }
+    }
+
+    let neighbourhood = world.get_settings().neighbourhood();
     for x in 0..width {
         for y in 0..height {
+            if *world.get_entity(x, y) == Entity::Wall {
+                continue;
+            }
             if rng.gen_ratio(1,3) {
-                let genome = create_random_entity(&mut rng);
-                let direction = Direction::by_value(rng.gen_range(0, 8));
+                let genome = Genome::new_random(&mut rng);
+                let direction = neighbourhood.by_value(rng.gen_range(0, neighbourhood.size()));
 
                 world.set_cell_ext(x, y, genome, direction);
             } else {
@@ -47,27 +70,26 @@ fn build_new_world() -> World {
     world
 }
 
-fn create_random_entity(rng: &mut ThreadRng) -> Genome {
-    let mut genome = Genome::new_plant();
-    let mut i = 0;
-    while i < GENOME_LENGTH - 4 {
-        let index = rng.gen_range(0, KNOWN_GENES.len());
-        let gene = KNOWN_GENES[index];
-        genome.mutate(i, gene);
-        i += 1;
+// JSON file with a list of regions
+fn load_regions(world: &mut World, path: &str) -> std::result::Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let regions: Vec<RegionInfo> = serde_json::from_str(&text).map_err(|error| error.to_string())?;
 
-        if gene == SENSE {
-            for _ in 0..2 {
-                genome.mutate(i, rng.gen_range(0, GENOME_LENGTH));
-                i += 1;
-            }
-        }
-        if gene == TURN {
-            genome.mutate(i, rng.gen_range(0, GENOME_LENGTH));
-            i += 1;
-        }
+    for info in regions.iter() {
+        world.add_region(info.as_region()?)?;
+    }
+    Ok(())
+}
+
+// JSON file with a list of scheduled settings changes
+fn load_schedule(world: &mut World, path: &str) -> std::result::Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let changes: Vec<ScheduledChangeInfo> = serde_json::from_str(&text).map_err(|error| error.to_string())?;
+
+    for info in changes.iter() {
+        world.schedule.add(info.as_change())?;
     }
-    genome
+    Ok(())
 }
 
 pub fn initialize_world() {
@@ -100,24 +122,173 @@ pub fn api_get_settings(_req: HttpRequest) -> Result<Json<SettingsInfo>>{
 }
 
 
-pub fn api_update_settings(json: Json<SettingsInfo>) -> impl Responder {
+pub fn api_update_settings(json: Json<SettingsInfo>) -> Result<HttpResponse> {
     let mut world = WORLD.lock().expect("Could not lock mutex");
-    let new_settings = json.as_settings();
+    let new_settings = json.as_settings().map_err(error::ErrorBadRequest)?;
 
     println!("API_UPDATE_SETTINGS: new settings: {:?}", new_settings);
     world.update_settings(new_settings);
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectionParams {
+    #[serde(rename = "type")]
+    projection: Option<String>
+}
+
+pub fn api_get_world(query: Query<ProjectionParams>) -> Result<Json<WorldInfo>> {
+    let world = WORLD.lock().unwrap();
+    let projection = projection_by_name(query.projection.as_ref().map(String::as_str));
+    Ok(Json(WorldInfo::from(&world, projection.as_ref())))
+}
+
+pub fn api_get_species(_req: HttpRequest) -> Result<Json<Vec<SpeciesInfo>>> {
+    let world = WORLD.lock().unwrap();
+    let species = world.species
+        .living()
+        .into_iter()
+        .map(SpeciesInfo::from)
+        .collect();
+    Ok(Json(species))
+}
+
+pub fn api_get_phylogeny_json(_req: HttpRequest) -> Result<Json<PhylogenyInfo>> {
+    let world = WORLD.lock().unwrap();
+    Ok(Json(PhylogenyInfo::from(&world)))
+}
+
+pub fn api_get_phylogeny_newick(_req: HttpRequest) -> impl Responder {
+    let world = WORLD.lock().unwrap();
+    HttpResponse::Ok()
+        .content_type("text/plain")
+        .body(world.phylogeny().to_newick())
+}
+
+pub fn api_get_regions(_req: HttpRequest) -> Result<Json<Vec<RegionInfo>>> {
+    let world = WORLD.lock().unwrap();
+    Ok(Json(world.regions.iter().map(RegionInfo::from).collect()))
+}
+
+pub fn api_add_region(json: Json<RegionInfo>) -> Result<HttpResponse> {
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+    let region = json.as_region().map_err(error::ErrorBadRequest)?;
+
+    println!("API_ADD_REGION: {:?}", region);
+    world.add_region(region).map_err(error::ErrorBadRequest)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub fn api_clear_regions(_req: HttpRequest) -> impl Responder {
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+
+    println!("API_CLEAR_REGIONS: done");
+    world.clear_regions();
     HttpResponse::Ok()
 }
 
-pub fn api_get_world(_req: HttpRequest) -> Result<Json<WorldInfo>> {
+pub fn api_get_schedule(_req: HttpRequest) -> Result<Json<Vec<ScheduledChangeInfo>>> {
     let world = WORLD.lock().unwrap();
-    let projection = GeneTypesProjection {};
-    Ok(Json(WorldInfo::from(&world, &projection)))
+    Ok(Json(world.schedule.changes.iter().map(ScheduledChangeInfo::from).collect()))
+}
+
+pub fn api_add_scheduled_change(json: Json<ScheduledChangeInfo>) -> Result<HttpResponse> {
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+    let change = json.as_change();
+
+    println!("API_ADD_SCHEDULED_CHANGE: {:?}", change);
+    world.schedule.add(change).map_err(error::ErrorBadRequest)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+pub fn api_clear_schedule(_req: HttpRequest) -> impl Responder {
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+
+    println!("API_CLEAR_SCHEDULE: done");
+    world.schedule.clear();
+    HttpResponse::Ok()
+}
+
+pub fn api_get_event_log(_req: HttpRequest) -> Result<Json<Vec<EventRecordInfo>>> {
+    let world = WORLD.lock().unwrap();
+    Ok(Json(world.events.log.iter().map(EventRecordInfo::from).collect()))
+}
+
+pub fn api_get_pending_events(_req: HttpRequest) -> Result<Json<Vec<ScheduledEventInfo>>> {
+    let world = WORLD.lock().unwrap();
+    let pending = world.events.pending
+        .iter()
+        .map(|(tick, kind)| ScheduledEventInfo { tick: *tick, event: EventInfo::from(kind) })
+        .collect();
+    Ok(Json(pending))
+}
+
+pub fn api_trigger_event(json: Json<EventInfo>) -> Result<Json<EventRecordInfo>> {
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+    let record = world.trigger_event(json.as_kind()).map_err(error::ErrorBadRequest)?;
+
+    println!("API_TRIGGER_EVENT: {:?}", record);
+    Ok(Json(EventRecordInfo::from(&record)))
+}
+
+pub fn api_schedule_event(json: Json<ScheduledEventInfo>) -> Result<HttpResponse> {
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+
+    println!("API_SCHEDULE_EVENT: {:?}", json);
+    world.events.schedule(json.tick, json.event.as_kind()).map_err(error::ErrorBadRequest)?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveTopParams { count: usize }
+
+pub fn api_get_archive_top(path: Path<ArchiveTopParams>) -> Result<Json<Vec<GenotypeInfo>>> {
+    let world = WORLD.lock().unwrap();
+    let genotypes = world.archive
+        .top(path.count)
+        .into_iter()
+        .map(GenotypeInfo::from)
+        .collect();
+    Ok(Json(genotypes))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveSearchParams { pattern: String }
+
+pub fn api_search_archive(query: Query<ArchiveSearchParams>) -> Result<Json<Vec<GenotypeInfo>>> {
+    let pattern = parse_gene_pattern(&query.pattern)
+        .ok_or_else(|| error::ErrorBadRequest("Pattern should be comma separated genes or *"))?;
+
+    let world = WORLD.lock().unwrap();
+    let genotypes = world.archive
+        .search(&pattern)
+        .into_iter()
+        .map(GenotypeInfo::from)
+        .collect();
+    Ok(Json(genotypes))
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CellCoordsParams { x: Coords, y: Coords }
 
+#[derive(Debug, Deserialize)]
+pub struct PaintParams { x: Coords, y: Coords, entity: String }
+
+/// Only walls could be painted or cleared, squares with cells and corpses are left as they are
+pub fn api_paint_entity(path: Path<PaintParams>) -> Result<HttpResponse> {
+    let params = path.into_inner();
+    let mut world = WORLD.lock().expect("Could not lock mutex");
+
+    match params.entity.as_str() {
+        "wall" => world.paint_wall(params.x, params.y, true),
+        "nothing" => world.paint_wall(params.x, params.y, false),
+        _ => return Err(error::ErrorBadRequest("Entity should be wall or nothing"))
+    };
+
+    println!("API_PAINT_ENTITY: {:?}", params);
+    Ok(HttpResponse::Ok().finish())
+}
+
 pub fn api_get_cell(path: Path<CellCoordsParams>) -> Result<Json<Option<CellInfo>>> {
     let coords = path.into_inner();
     let world = WORLD.lock().unwrap();
@@ -131,10 +302,15 @@ pub fn api_get_cell(path: Path<CellCoordsParams>) -> Result<Json<Option<CellInfo
                 x: coords.x,
                 y: coords.y,
                 health: entity_state.health,
-                direction: entity_state.direction as usize,
+                direction: world.get_settings().neighbourhood().normalize(entity_state.direction) as usize,
                 genome_id: genome.id,
-                genome: genome.genes.to_vec()
-
+                genome: genome.genes.to_vec(),
+                genome_hash: genome.hash,
+                same_genome_count: world.genomes.count_same(*genome_id),
+                parent_id: genome.parent_id,
+                birth_tick: genome.birth_tick,
+                age: entity_state.age,
+                registers: entity_state.registers.to_vec()
             })
         },
         //TODO: what to return if there is no cell
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use morphoid::types::*;
@@ -12,11 +14,16 @@ pub struct WorldInfo {
 }
 
 impl WorldInfo {
-    pub fn from<P : Projection>(world: &World, projection: &P) -> WorldInfo {
+    pub fn from<P : Projection + ?Sized>(world: &World, projection: &P) -> WorldInfo {
         let entities_info = world
             .entities
             .iter()
-            .map(|entity| projection.from(entity, &world))
+            .enumerate()
+            .map(|(index, entity)| {
+                let x = index as Coords % world.width;
+                let y = index as Coords / world.width;
+                projection.from(x, y, entity, &world)
+            })
             .collect();
 
         WorldInfo {
@@ -48,7 +55,7 @@ impl ProjectionRowMeta {
 // Projection actually could return the whole entity info object
 // which could be anything or part of enum
 pub trait Projection {
-    fn from(&self, entity: &Entity, world: &World) -> Vec<String>;
+    fn from(&self, x: Coords, y: Coords, entity: &Entity, world: &World) -> Vec<String>;
     fn meta(&self) -> Vec<ProjectionRowMeta>;
 }
 
@@ -66,7 +73,7 @@ impl Projection for GeneTypesProjection {
         ]
     }
 
-    fn from(&self, entity: &Entity, world: &World) -> Vec<String> {
+    fn from(&self, _x: Coords, _y: Coords, entity: &Entity, world: &World) -> Vec<String> {
         match entity {
             Entity::Nothing => vec![String::from("nothing")],
             Entity::Cell(genome_id) => {
@@ -81,7 +88,142 @@ impl Projection for GeneTypesProjection {
                     state.health.to_string()
                 ]
             },
-            Entity::Corpse(_) => vec![String::from("corpse")]
+            Entity::Corpse(_) => vec![String::from("corpse")],
+            Entity::Wall => vec![String::from("wall")]
+        }
+    }
+}
+
+pub struct SpeciesProjection;
+impl Projection for SpeciesProjection {
+    fn meta(&self) -> Vec<ProjectionRowMeta> {
+        vec![
+            ProjectionRowMeta::new("type", "Type of cell", true),
+            ProjectionRowMeta::new("species", "Species of the cell, by genome similarity", false),
+        ]
+    }
+
+    fn from(&self, _x: Coords, _y: Coords, entity: &Entity, world: &World) -> Vec<String> {
+        match entity {
+            Entity::Nothing => vec![String::from("nothing")],
+            Entity::Cell(genome_id) => {
+                let species = world.species
+                    .species_of(*genome_id)
+                    .map_or(String::from("unknown"), |species_id| species_id.to_string());
+                vec![String::from("cell"), species]
+            },
+            Entity::Corpse(_) => vec![String::from("corpse")],
+            Entity::Wall => vec![String::from("wall")]
+        }
+    }
+}
+
+pub struct LightProjection;
+impl Projection for LightProjection {
+    fn meta(&self) -> Vec<ProjectionRowMeta> {
+        vec![
+            ProjectionRowMeta::new("type", "Type of entity", true),
+            ProjectionRowMeta::new("light", "Share of photosynthesis available at the square", true),
+        ]
+    }
+
+    fn from(&self, x: Coords, y: Coords, entity: &Entity, world: &World) -> Vec<String> {
+        vec![entity_type(entity), format!("{:.2}", world.light(x, y))]
+    }
+}
+
+pub struct NutrientsProjection;
+impl Projection for NutrientsProjection {
+    fn meta(&self) -> Vec<ProjectionRowMeta> {
+        vec![
+            ProjectionRowMeta::new("type", "Type of entity", true),
+            ProjectionRowMeta::new("nutrients", "Nutrients in the soil of the square", true),
+        ]
+    }
+
+    fn from(&self, x: Coords, y: Coords, entity: &Entity, world: &World) -> Vec<String> {
+        vec![entity_type(entity), format!("{:.2}", world.nutrients_at(x, y))]
+    }
+}
+
+pub struct SignalsProjection;
+impl Projection for SignalsProjection {
+    fn meta(&self) -> Vec<ProjectionRowMeta> {
+        let mut meta = vec![ProjectionRowMeta::new("type", "Type of entity", true)];
+        for channel in 0..SIGNAL_CHANNELS {
+            meta.push(ProjectionRowMeta::new(
+                &format!("signal{}", channel),
+                &format!("Concentration of signal in channel {}", channel),
+                true
+            ));
+        }
+        meta
+    }
+
+    fn from(&self, x: Coords, y: Coords, entity: &Entity, world: &World) -> Vec<String> {
+        let mut result = vec![entity_type(entity)];
+        for channel in 0..SIGNAL_CHANNELS {
+            result.push(format!("{:.2}", world.signal(x, y, channel)));
+        }
+        result
+    }
+}
+
+pub struct AgeProjection;
+impl Projection for AgeProjection {
+    fn meta(&self) -> Vec<ProjectionRowMeta> {
+        vec![
+            ProjectionRowMeta::new("type", "Type of entity", true),
+            ProjectionRowMeta::new("age", "Ticks since the birth of the cell", true),
+        ]
+    }
+
+    fn from(&self, _x: Coords, _y: Coords, entity: &Entity, world: &World) -> Vec<String> {
+        match entity {
+            Entity::Cell(genome_id) => vec![entity_type(entity), world.get_state(*genome_id).age.to_string()],
+            _ => vec![entity_type(entity)]
+        }
+    }
+}
+
+pub fn entity_type(entity: &Entity) -> String {
+    match entity {
+        Entity::Nothing => String::from("nothing"),
+        Entity::Cell(_) => String::from("cell"),
+        Entity::Corpse(_) => String::from("corpse"),
+        Entity::Wall => String::from("wall")
+    }
+}
+
+/// Gene types are shown by default
+pub fn projection_by_name(name: Option<&str>) -> Box<dyn Projection> {
+    match name {
+        Some("species") => Box::new(SpeciesProjection {}),
+        Some("light") => Box::new(LightProjection {}),
+        Some("nutrients") => Box::new(NutrientsProjection {}),
+        Some("signals") => Box::new(SignalsProjection {}),
+        Some("age") => Box::new(AgeProjection {}),
+        _ => Box::new(GeneTypesProjection {})
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpeciesInfo {
+    pub id: u64,
+    pub size: usize,
+    pub representative: Vec<usize>,
+    pub birth_tick: u64,
+    pub extinction_tick: Option<u64>
+}
+
+impl SpeciesInfo {
+    pub fn from(species: &Species) -> SpeciesInfo {
+        SpeciesInfo {
+            id: species.id,
+            size: species.size,
+            representative: species.representative.to_vec(),
+            birth_tick: species.birth_tick,
+            extinction_tick: species.extinction_tick
         }
     }
 }
@@ -102,6 +244,40 @@ pub struct SettingsInfo {
     pub corpse_decay: HealthType,
     pub corpse_initial: HealthType,
     pub mutation_probability: f64,
+    pub species_distance: String,
+    pub species_threshold: usize,
+    pub topology: String,
+    pub neighbourhood: String,
+    pub inherit_registers: bool,
+    pub light_depth_falloff: f64,
+    pub day_length: u64,
+    pub night_light: f64,
+    pub season_length: u64,
+    pub season_amplitude: f64,
+    pub sunspot_size: Coords,
+    pub sunspot_contrast: f64,
+    pub nutrient_diffusion: f64,
+    pub absorb_adds: HealthType,
+    pub absorb_cost: HealthType,
+    pub emit_amount: f64,
+    pub emit_cost: HealthType,
+    pub smell_cost: HealthType,
+    pub signal_diffusion: f64,
+    pub signal_evaporation: f64,
+    pub immigration_rate: usize,
+    pub reseed_floor: usize,
+    pub max_lifespan: u64,
+    pub age_upkeep: f64,
+    pub metabolism_cost: HealthType,
+    pub max_health: HealthType,
+    pub defend_cost: HealthType,
+    pub defend_duration: u64,
+    pub defend_reduction: f64,
+    pub look_range: Coords,
+    pub look_cost: HealthType,
+    pub health_overflow: String,
+    pub metabolism_scaling: String,
+    pub brain: String,
 }
 
 impl SettingsInfo {
@@ -121,11 +297,53 @@ impl SettingsInfo {
             corpse_decay: settings.corpse_decay,
             corpse_initial: settings.corpse_initial,
             mutation_probability: settings.mutation_probability,
+            species_distance: settings.species_distance.name().to_string(),
+            species_threshold: settings.species_threshold,
+            topology: settings.topology.name().to_string(),
+            neighbourhood: settings.neighbourhood.name().to_string(),
+            inherit_registers: settings.inherit_registers,
+            light_depth_falloff: settings.light_depth_falloff,
+            day_length: settings.day_length,
+            night_light: settings.night_light,
+            season_length: settings.season_length,
+            season_amplitude: settings.season_amplitude,
+            sunspot_size: settings.sunspot_size,
+            sunspot_contrast: settings.sunspot_contrast,
+            nutrient_diffusion: settings.nutrient_diffusion,
+            absorb_adds: settings.absorb_adds,
+            absorb_cost: settings.absorb_cost,
+            emit_amount: settings.emit_amount,
+            emit_cost: settings.emit_cost,
+            smell_cost: settings.smell_cost,
+            signal_diffusion: settings.signal_diffusion,
+            signal_evaporation: settings.signal_evaporation,
+            immigration_rate: settings.immigration_rate,
+            reseed_floor: settings.reseed_floor,
+            max_lifespan: settings.max_lifespan,
+            age_upkeep: settings.age_upkeep,
+            metabolism_cost: settings.metabolism_cost,
+            max_health: settings.max_health,
+            defend_cost: settings.defend_cost,
+            defend_duration: settings.defend_duration,
+            defend_reduction: settings.defend_reduction,
+            look_range: settings.look_range,
+            look_cost: settings.look_cost,
+            health_overflow: settings.health_overflow.name().to_string(),
+            metabolism_scaling: settings.metabolism_scaling.name().to_string(),
+            brain: settings.brain.name().to_string(),
         }
     }
 
-    pub fn as_settings(&self) -> Settings {
-        SettingsBuilder::prod()
+    /// Unknown names are errors, a typo should not silently become the default
+    pub fn as_settings(&self) -> Result<Settings, String> {
+        let species_distance = by_name("species distance", &self.species_distance, GenomeDistance::by_name)?;
+        let topology = by_name("topology", &self.topology, Topology::by_name)?;
+        let neighbourhood = by_name("neighbourhood", &self.neighbourhood, Neighbourhood::by_name)?;
+        let health_overflow = by_name("health overflow", &self.health_overflow, HealthOverflow::by_name)?;
+        let metabolism_scaling = by_name("metabolism scaling", &self.metabolism_scaling, MetabolismScaling::by_name)?;
+        let brain = by_name("brain", &self.brain, BrainKind::by_name)?;
+
+        let settings = SettingsBuilder::prod()
             .with_reproduce_cost(self.reproduce_cost)
             //.with_reproduce_threshold(self.reproduce_threshold)
             .with_photosynthesis_adds(self.photosynthesis_adds)
@@ -140,10 +358,49 @@ impl SettingsInfo {
             .with_corpse_decay(self.corpse_decay)
             .with_corpse_initial(self.corpse_initial)
             .with_mutation_probability(self.mutation_probability)
-            .build()
+            .with_species_distance(species_distance)
+            .with_species_threshold(self.species_threshold)
+            .with_topology(topology)
+            .with_neighbourhood(neighbourhood)
+            .with_inherit_registers(self.inherit_registers)
+            .with_light_depth_falloff(self.light_depth_falloff)
+            .with_day_length(self.day_length)
+            .with_night_light(self.night_light)
+            .with_season_length(self.season_length)
+            .with_season_amplitude(self.season_amplitude)
+            .with_sunspot_size(self.sunspot_size)
+            .with_sunspot_contrast(self.sunspot_contrast)
+            .with_nutrient_diffusion(self.nutrient_diffusion)
+            .with_absorb_adds(self.absorb_adds)
+            .with_absorb_cost(self.absorb_cost)
+            .with_emit_amount(self.emit_amount)
+            .with_emit_cost(self.emit_cost)
+            .with_smell_cost(self.smell_cost)
+            .with_signal_diffusion(self.signal_diffusion)
+            .with_signal_evaporation(self.signal_evaporation)
+            .with_immigration_rate(self.immigration_rate)
+            .with_reseed_floor(self.reseed_floor)
+            .with_max_lifespan(self.max_lifespan)
+            .with_age_upkeep(self.age_upkeep)
+            .with_metabolism_cost(self.metabolism_cost)
+            .with_max_health(self.max_health)
+            .with_defend_cost(self.defend_cost)
+            .with_defend_duration(self.defend_duration)
+            .with_defend_reduction(self.defend_reduction)
+            .with_look_range(self.look_range)
+            .with_look_cost(self.look_cost)
+            .with_health_overflow(health_overflow)
+            .with_metabolism_scaling(metabolism_scaling)
+            .with_brain(brain)
+            .build();
+        Ok(settings)
     }
 }
 
+fn by_name<T>(kind: &str, name: &str, parse: fn(&str) -> Option<T>) -> Result<T, String> {
+    parse(name).ok_or_else(|| format!("Unknown {} {:?}", kind, name))
+}
+
 // TODO: need better name
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CellInfo {
@@ -152,7 +409,266 @@ pub struct CellInfo {
     pub health: i32,
     pub direction: usize,
     pub genome_id: u64,
-    pub genome: Vec<usize>
+    pub genome: Vec<usize>,
+    pub genome_hash: u64,
+    pub same_genome_count: usize,
+    pub parent_id: Option<u64>,
+    pub birth_tick: u64,
+    pub age: u64,
+    pub registers: Vec<i32>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MutationInfo {
+    pub index: usize,
+    pub old_gene: usize,
+    pub new_gene: usize
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PhyloNodeInfo {
+    pub genome_id: u64,
+    pub alive: bool,
+    pub branch_length: u64,
+    pub mutations: Vec<MutationInfo>,
+    pub population: usize,
+    pub category: String,
+    pub parent: Option<u64>
+}
+
+impl PhyloNodeInfo {
+    pub fn from(node: &PhyloNode, parent: Option<GenomeId>) -> PhyloNodeInfo {
+        PhyloNodeInfo {
+            genome_id: node.genome_id,
+            alive: node.alive,
+            branch_length: node.branch_length,
+            mutations: node.mutations
+                .iter()
+                .map(|mutation| MutationInfo {
+                    index: mutation.index,
+                    old_gene: mutation.old_gene,
+                    new_gene: mutation.new_gene
+                })
+                .collect(),
+            population: node.population,
+            category: node.category.to_string(),
+            parent
+        }
+    }
+}
+
+// Nodes are flat rows pointing to their parents, parents go before their children.
+// Deep lineages would overflow the stack as nested objects
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PhylogenyInfo {
+    pub tick: u64,
+    pub nodes: Vec<PhyloNodeInfo>
+}
+
+impl PhylogenyInfo {
+    pub fn from(world: &World) -> PhylogenyInfo {
+        PhylogenyInfo::from_phylogeny(world.tick_no, &world.phylogeny())
+    }
+
+    pub fn from_phylogeny(tick: u64, phylogeny: &Phylogeny) -> PhylogenyInfo {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<(&PhyloNode, Option<GenomeId>)> = phylogeny.roots
+            .iter()
+            .rev()
+            .map(|root| (root, None))
+            .collect();
+        while let Some((node, parent)) = stack.pop() {
+            nodes.push(PhyloNodeInfo::from(node, parent));
+            stack.extend(node.children.iter().rev().map(|child| (child, Some(node.genome_id))));
+        }
+        PhylogenyInfo { tick, nodes }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenotypeInfo {
+    pub hash: u64,
+    pub genome: Vec<usize>,
+    pub first_seen_tick: u64,
+    pub last_seen_tick: u64,
+    pub population: usize,
+    pub peak_population: usize,
+    pub total_offspring: usize,
+    pub deaths: HashMap<String, usize>
+}
+
+impl GenotypeInfo {
+    pub fn from(record: &GenotypeRecord) -> GenotypeInfo {
+        GenotypeInfo {
+            hash: record.hash,
+            genome: record.genes.to_vec(),
+            first_seen_tick: record.first_seen_tick,
+            last_seen_tick: record.last_seen_tick,
+            population: record.population,
+            peak_population: record.peak_population,
+            total_offspring: record.total_offspring,
+            deaths: record.deaths
+                .iter()
+                .map(|(cause, count)| (cause.name().to_string(), *count))
+                .collect()
+        }
+    }
+}
+
+/// Either `rect` as [x, y, width, height] or `mask` as map-like text with `#` should be set
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegionInfo {
+    pub name: String,
+    pub rect: Option<Vec<Coords>>,
+    pub mask: Option<String>,
+    pub overrides: HashMap<String, f64>
+}
+
+impl RegionInfo {
+    pub fn from(region: &Region) -> RegionInfo {
+        let (rect, mask) = match &region.area {
+            RegionArea::Rect { x, y, width, height } => (Some(vec![*x, *y, *width, *height]), None),
+            RegionArea::Mask(rows) => {
+                let text = rows
+                    .iter()
+                    .map(|row| row.iter().map(|inside| if *inside { '#' } else { '.' }).collect::<String>())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                (None, Some(text))
+            }
+        };
+
+        RegionInfo {
+            name: region.name.clone(),
+            rect,
+            mask,
+            overrides: region.overrides.iter().cloned().collect()
+        }
+    }
+
+    pub fn as_region(&self) -> Result<Region, String> {
+        let area = match (&self.rect, &self.mask) {
+            (Some(rect), None) if rect.len() == 4 =>
+                RegionArea::Rect { x: rect[0], y: rect[1], width: rect[2], height: rect[3] },
+            (None, Some(mask)) => RegionArea::mask_from_text(mask),
+            _ => return Err(format!("Region {:?} should have either rect of 4 numbers or mask", self.name))
+        };
+
+        let mut overrides: Vec<(String, f64)> = self.overrides
+            .iter()
+            .map(|(name, value)| (name.clone(), *value))
+            .collect();
+        overrides.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(Region::new(&self.name, area, overrides))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ScheduleShapeInfo {
+    Step { value: f64 },
+    Ramp { from: f64, to: f64, duration: u64 },
+    Periodic { base: f64, amplitude: f64, period: u64 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledChangeInfo {
+    pub setting: String,
+    pub start_tick: u64,
+    pub shape: ScheduleShapeInfo
+}
+
+impl ScheduledChangeInfo {
+    pub fn from(change: &ScheduledChange) -> ScheduledChangeInfo {
+        let shape = match change.shape {
+            ScheduleShape::Step { value } => ScheduleShapeInfo::Step { value },
+            ScheduleShape::Ramp { from, to, duration } => ScheduleShapeInfo::Ramp { from, to, duration },
+            ScheduleShape::Periodic { base, amplitude, period } =>
+                ScheduleShapeInfo::Periodic { base, amplitude, period },
+        };
+
+        ScheduledChangeInfo {
+            setting: change.setting.clone(),
+            start_tick: change.start_tick,
+            shape
+        }
+    }
+
+    pub fn as_change(&self) -> ScheduledChange {
+        let shape = match self.shape {
+            ScheduleShapeInfo::Step { value } => ScheduleShape::Step { value },
+            ScheduleShapeInfo::Ramp { from, to, duration } => ScheduleShape::Ramp { from, to, duration },
+            ScheduleShapeInfo::Periodic { base, amplitude, period } =>
+                ScheduleShape::Periodic { base, amplitude, period },
+        };
+        ScheduledChange::new(&self.setting, self.start_tick, shape)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventInfo {
+    Wipe { width: Coords, height: Coords },
+    Cull { share: f64 },
+    MutationSpike { probability: f64, duration: u64 },
+    FlipLight,
+}
+
+impl EventInfo {
+    pub fn from(kind: &EventKind) -> EventInfo {
+        match *kind {
+            EventKind::Wipe { width, height } => EventInfo::Wipe { width, height },
+            EventKind::Cull { share } => EventInfo::Cull { share },
+            EventKind::MutationSpike { probability, duration } => EventInfo::MutationSpike { probability, duration },
+            EventKind::FlipLight => EventInfo::FlipLight,
+        }
+    }
+
+    pub fn as_kind(&self) -> EventKind {
+        match *self {
+            EventInfo::Wipe { width, height } => EventKind::Wipe { width, height },
+            EventInfo::Cull { share } => EventKind::Cull { share },
+            EventInfo::MutationSpike { probability, duration } => EventKind::MutationSpike { probability, duration },
+            EventInfo::FlipLight => EventKind::FlipLight,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduledEventInfo {
+    pub tick: u64,
+    pub event: EventInfo
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventRecordInfo {
+    pub tick: u64,
+    pub event: EventInfo,
+    pub killed: usize
+}
+
+impl EventRecordInfo {
+    pub fn from(record: &EventRecord) -> EventRecordInfo {
+        EventRecordInfo {
+            tick: record.tick,
+            event: EventInfo::from(&record.kind),
+            killed: record.killed
+        }
+    }
+}
+
+/// Comma separated genes, `*` matches any gene: "26,*,28"
+pub fn parse_gene_pattern(source: &str) -> Option<Vec<Option<Gene>>> {
+    source
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| match item {
+            "*" => Some(None),
+            _ => item.parse::<Gene>().ok().map(Some)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -187,7 +703,159 @@ mod tests {
         assert_eq!(world_info.data[5], fixture(vec!["cell", "0", "0", "0", "64"]));
     }
 
+    #[test]
+    fn test_species_projection() {
+        let mut world = World::new(3, 1, SettingsBuilder::zero());
+        world.set_cell(0, 0, Genome::new_plant());
+        world.set_cell(1, 0, Genome::new_plant());
+        world.set_cell(2, 0, Genome::new_predator());
+
+        let projection = projection_by_name(Some("species"));
+        assert_eq!(projection.from(0, 0, world.get_entity(0, 0), &world), fixture(vec!["cell", "unknown"]));
+
+        world.tick(&mut Processor::new());
+
+        let world_info = WorldInfo::from(&world, projection.as_ref());
+        assert_eq!(world_info.data[0], world_info.data[1]);
+        assert_ne!(world_info.data[0], world_info.data[2]);
+    }
+
+    #[test]
+    fn test_light_projection() {
+        let settings = SettingsBuilder::prod().with_light_depth_falloff(0.5).build();
+        let mut world = World::new(1, 2, settings);
+        world.set_cell(0, 1, Genome::new_plant());
+
+        let world_info = WorldInfo::from(&world, projection_by_name(Some("light")).as_ref());
+        assert_eq!(world_info.data[0], fixture(vec!["nothing", "1.00"]));
+        assert_eq!(world_info.data[1], fixture(vec!["cell", "0.50"]));
+    }
+
+    #[test]
+    fn test_age_projection() {
+        let mut world = World::new(2, 1, SettingsBuilder::zero());
+        world.set_cell(1, 0, Genome::new_plant());
+        world.tick(&mut Processor::new());
+
+        let world_info = WorldInfo::from(&world, projection_by_name(Some("age")).as_ref());
+        assert_eq!(world_info.data[0], fixture(vec!["nothing"]));
+        assert_eq!(world_info.data[1], fixture(vec!["cell", "1"]));
+    }
+
+    #[test]
+    fn test_signals_projection() {
+        let mut world = World::prod(2, 1);
+        world.add_signal(1, 0, 1, 2.5);
+
+        let projection = projection_by_name(Some("signals"));
+        let world_info = WorldInfo::from(&world, projection.as_ref());
+        assert_eq!(1 + SIGNAL_CHANNELS, world_info.meta.len());
+        assert_eq!(world_info.data[0], fixture(vec!["nothing", "0.00", "0.00"]));
+        assert_eq!(world_info.data[1], fixture(vec!["nothing", "0.00", "2.50"]));
+    }
+
+    #[test]
+    fn test_phylogeny_info() {
+        let mut world = World::new(2, 1, SettingsBuilder::zero());
+        world.set_cell_ext(0, 0, Genome::new_yeast(), Direction::East);
+        world.tick(&mut Processor::new());
+
+        let info = PhylogenyInfo::from(&world);
+        assert_eq!(1, info.tick);
+        assert_eq!(2, info.nodes.len());
+        assert_eq!(None, info.nodes[0].parent);
+        assert_eq!(2, info.nodes[0].population);
+        assert_eq!("*", info.nodes[0].category);
+        assert_eq!(Some(info.nodes[0].genome_id), info.nodes[1].parent);
+    }
+
+    #[test]
+    fn test_deep_phylogeny_info() {
+        let mut lineage = LineageStorage::new();
+        let mut genome = Genome::new_plant();
+        lineage.born(&genome);
+        for tick in 1..100_000 {
+            genome = genome.new_child();
+            genome.birth_tick = tick;
+            lineage.born(&genome);
+        }
+
+        let info = PhylogenyInfo::from_phylogeny(100_000, &lineage.phylogeny());
+        assert_eq!(100_000, info.nodes.len());
+        assert_eq!(Some(genome.id()), info.nodes.last().map(|node| node.genome_id));
+        assert!(serde_json::to_string(&info).is_ok());
+    }
+
+    #[test]
+    fn test_parse_gene_pattern() {
+        assert_eq!(Some(vec![Some(26), None, Some(28)]), parse_gene_pattern("26,*, 28"));
+        assert_eq!(Some(vec![]), parse_gene_pattern(""));
+        assert_eq!(None, parse_gene_pattern("26,move"));
+    }
+
     fn fixture(source: Vec<&str>) -> Vec<String> {
         source.iter().map(|e| e.to_string()).collect()
     }
+
+    #[test]
+    fn test_settings_info() {
+        let settings = SettingsBuilder::prod()
+            .with_neighbourhood(Neighbourhood::VonNeumann)
+            .with_brain(BrainKind::Neural)
+            .build();
+        let mut info = SettingsInfo::from(&settings);
+        let parsed = info.as_settings().unwrap();
+        assert_eq!(Neighbourhood::VonNeumann, parsed.neighbourhood());
+        assert_eq!(BrainKind::Neural, parsed.brain());
+
+        info.neighbourhood = "von_neuman".to_string();
+        assert!(info.as_settings().unwrap_err().contains("von_neuman"));
+
+        info = SettingsInfo::from(&settings);
+        info.brain = "lizard".to_string();
+        assert!(info.as_settings().is_err());
+    }
+
+    #[test]
+    fn test_region_info() {
+        let json = r#"{"name": "swamp", "rect": [1, 2, 3, 4], "mask": null, "overrides": {"move_cost": -20}}"#;
+        let info: RegionInfo = serde_json::from_str(json).unwrap();
+        let region = info.as_region().unwrap();
+
+        assert_eq!(RegionArea::Rect { x: 1, y: 2, width: 3, height: 4 }, region.area);
+        assert_eq!(vec![("move_cost".to_string(), -20.0)], region.overrides);
+
+        let mask = Region::new("lake", RegionArea::mask_from_text(".#\n#."), vec![]);
+        assert_eq!(Some(".#\n#.".to_string()), RegionInfo::from(&mask).mask);
+
+        let broken = RegionInfo { name: "broken".to_string(), rect: Some(vec![1]), mask: None, overrides: HashMap::new() };
+        assert!(broken.as_region().is_err());
+    }
+
+    #[test]
+    fn test_scheduled_change_info() {
+        let json = r#"{"setting": "night_light", "start_tick": 100, "shape": {"kind": "ramp", "from": 1.0, "to": 0.2, "duration": 50}}"#;
+        let info: ScheduledChangeInfo = serde_json::from_str(json).unwrap();
+        let change = info.as_change();
+
+        assert_eq!(ScheduleShape::Ramp { from: 1.0, to: 0.2, duration: 50 }, change.shape);
+        assert_eq!(100, change.start_tick);
+
+        let json = serde_json::to_string(&ScheduledChangeInfo::from(&change)).unwrap();
+        assert!(json.contains(r#""kind":"ramp""#));
+    }
+
+    #[test]
+    fn test_event_info() {
+        let info: ScheduledEventInfo =
+            serde_json::from_str(r#"{"tick": 10, "event": {"kind": "cull", "share": 0.3}}"#).unwrap();
+        assert_eq!(EventKind::Cull { share: 0.3 }, info.event.as_kind());
+
+        let info: EventInfo = serde_json::from_str(r#"{"kind": "flip_light"}"#).unwrap();
+        assert_eq!(EventKind::FlipLight, info.as_kind());
+
+        let record = EventRecord { tick: 5, kind: EventKind::Wipe { width: 2, height: 3 }, killed: 4 };
+        let json = serde_json::to_string(&EventRecordInfo::from(&record)).unwrap();
+        assert_eq!(r#"{"tick":5,"event":{"kind":"wipe","width":2,"height":3},"killed":4}"#, json);
+    }
 }
@@ -25,6 +25,22 @@ fn main() -> std::io::Result<()> {
             .service(web::resource("/world/settings/get").route(web::get().to(api_get_settings)))
             .service(web::resource("/world/settings/update").route(web::post().to(api_update_settings)))
             .service(web::resource("/world/get").route(web::get().to(api_get_world)))
+            .service(web::resource("/world/species").route(web::get().to(api_get_species)))
+            .service(web::resource("/world/phylogeny/json").route(web::get().to(api_get_phylogeny_json)))
+            .service(web::resource("/world/phylogeny/newick").route(web::get().to(api_get_phylogeny_newick)))
+            .service(web::resource("/world/regions").route(web::get().to(api_get_regions)))
+            .service(web::resource("/world/regions/add").route(web::post().to(api_add_region)))
+            .service(web::resource("/world/regions/clear").route(web::post().to(api_clear_regions)))
+            .service(web::resource("/world/schedule").route(web::get().to(api_get_schedule)))
+            .service(web::resource("/world/schedule/add").route(web::post().to(api_add_scheduled_change)))
+            .service(web::resource("/world/schedule/clear").route(web::post().to(api_clear_schedule)))
+            .service(web::resource("/world/events").route(web::get().to(api_get_event_log)))
+            .service(web::resource("/world/events/pending").route(web::get().to(api_get_pending_events)))
+            .service(web::resource("/world/events/trigger").route(web::post().to(api_trigger_event)))
+            .service(web::resource("/world/events/schedule").route(web::post().to(api_schedule_event)))
+            .service(web::resource("/archive/top/{count}").route(web::get().to(api_get_archive_top)))
+            .service(web::resource("/archive/search").route(web::get().to(api_search_archive)))
+            .service(web::resource("/entity/{x}/{y}/paint/{entity}").route(web::post().to(api_paint_entity)))
             .service(web::resource("/entity/{x}/{y}").route(web::get().to(api_get_cell)))
             .service(web::resource("/world/reset").route(web::post().to(api_reset_world)))
             .service(
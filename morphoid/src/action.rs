@@ -34,14 +34,18 @@ impl Action for UpdateHealthAction {
 
 impl ReproduceAction {
     pub fn new(x: Coords, y: Coords) -> ReproduceAction {
-        ReproduceAction { x, y }
+        ReproduceAction { x, y, rotation: 0 }
+    }
+
+    pub fn towards(x: Coords, y: Coords, rotation: Gene) -> ReproduceAction {
+        ReproduceAction { x, y, rotation }
     }
 }
 
 impl Action for ReproduceAction {
     fn execute(&self, affector: &mut Affector) {
         affector.punish_for_action(self.x, self.y, REPRODUCE);
-        affector.reproduce(self.x, self.y);
+        affector.reproduce_towards(self.x, self.y, self.rotation);
     }
 }
 
@@ -49,14 +53,18 @@ impl Action for ReproduceAction {
 
 impl AttackAction {
     pub fn new(x: Coords, y: Coords, damage: HealthType) -> AttackAction {
-        AttackAction { x, y, damage }
+        AttackAction { x, y, rotation: 0, damage }
+    }
+
+    pub fn towards(x: Coords, y: Coords, rotation: Gene, damage: HealthType) -> AttackAction {
+        AttackAction { x, y, rotation, damage }
     }
 }
 
 impl Action for AttackAction {
     fn execute(&self, affector: &mut Affector) {
         affector.punish_for_action(self.x, self.y, ATTACK);
-        affector.attack(self.x, self.y, self.damage);
+        affector.attack_towards(self.x, self.y, self.rotation, self.damage);
     }
 }
 
@@ -64,14 +72,18 @@ impl Action for AttackAction {
 
 impl MoveAction {
     pub fn new(x: Coords, y: Coords) -> MoveAction {
-        MoveAction { x, y }
+        MoveAction { x, y, rotation: 0 }
+    }
+
+    pub fn towards(x: Coords, y: Coords, rotation: Gene) -> MoveAction {
+        MoveAction { x, y, rotation }
     }
 }
 
 impl Action for MoveAction {
     fn execute(&self, affector: &mut Affector) {
         affector.punish_for_action(self.x, self.y, MOVE);
-        affector.move_cell(self.x, self.y);
+        affector.move_cell_towards(self.x, self.y, self.rotation);
     }
 }
 
@@ -122,6 +134,80 @@ impl Action for DecayAction {
 
 // --------------------------------
 
+impl AbsorbAction {
+    pub fn new(x: Coords, y: Coords, amount: HealthType) -> AbsorbAction {
+        AbsorbAction { x, y, amount }
+    }
+}
+
+impl Action for AbsorbAction {
+    fn execute(&self, affector: &mut dyn Affector) {
+        affector.punish_for_action(self.x, self.y, ABSORB);
+        affector.absorb(self.x, self.y, self.amount);
+    }
+}
+
+// --------------------------------
+
+impl SetRegistersAction {
+    pub fn new(x: Coords, y: Coords, registers: Registers) -> SetRegistersAction {
+        SetRegistersAction { x, y, registers }
+    }
+}
+
+impl Action for SetRegistersAction {
+    fn execute(&self, affector: &mut dyn Affector) {
+        affector.set_registers(self.x, self.y, self.registers);
+    }
+}
+
+// --------------------------------
+
+impl DefendAction {
+    pub fn new(x: Coords, y: Coords, duration: Tick) -> DefendAction {
+        DefendAction { x, y, duration }
+    }
+}
+
+impl Action for DefendAction {
+    fn execute(&self, affector: &mut dyn Affector) {
+        affector.punish_for_action(self.x, self.y, DEFEND);
+        affector.defend(self.x, self.y, self.duration);
+    }
+}
+
+// --------------------------------
+
+impl EmitAction {
+    pub fn new(x: Coords, y: Coords, channel: usize, amount: f64) -> EmitAction {
+        EmitAction { x, y, channel, amount }
+    }
+}
+
+impl Action for EmitAction {
+    fn execute(&self, affector: &mut dyn Affector) {
+        affector.punish_for_action(self.x, self.y, EMIT);
+        affector.emit(self.x, self.y, self.channel, self.amount);
+    }
+}
+
+// --------------------------------
+
+impl SmellAction {
+    pub fn new(x: Coords, y: Coords, value: Gene) -> SmellAction {
+        SmellAction { x, y, value }
+    }
+}
+
+impl Action for SmellAction {
+    fn execute(&self, affector: &mut dyn Affector) {
+        affector.punish_for_action(self.x, self.y, SMELL);
+        affector.rotate_cell(self.x, self.y, self.value);
+    }
+}
+
+// --------------------------------
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -301,5 +387,49 @@ mod tests {
             Entity::Corpse(remains) => assert_eq!(7, *remains),
             _ => panic!("Corpse should be here"),
         }
+        assert_eq!(3.0, world.nutrients_at(0, 0));
+    }
+
+    #[test]
+    fn test_absorb() {
+        let settings = SettingsBuilder::prod()
+            .with_initial_cell_health(10)
+            .with_absorb_cost(-1)
+            .build();
+        let mut world = World::new(1, 1, settings);
+        world.set_cell(0, 0, Genome::new_plant());
+        world.add_nutrients(0, 0, 3.5);
+
+        Processor::new().apply(
+            &vec![Box::new(AbsorbAction::new(0, 0, 5))],
+            &mut world
+        );
+
+        assert_eq!(12, world.get_state_by_pos(0, 0).unwrap().health);
+        assert_eq!(0.5, world.nutrients_at(0, 0));
+    }
+
+    #[test]
+    fn test_defend() {
+        let settings = SettingsBuilder::prod()
+            .with_initial_cell_health(20)
+            .with_attack_cost(0)
+            .with_defend_cost(-2)
+            .with_defend_reduction(0.5)
+            .build();
+        let mut world = World::new(2, 1, settings);
+        world.set_cell_ext(0, 0, Genome::new_predator(), Direction::East);
+        world.set_cell(1, 0, Genome::new_plant());
+
+        Processor::new().apply(
+            &vec![
+                Box::new(DefendAction::new(1, 0, 2)),
+                Box::new(AttackAction::new(0, 0, 10))
+            ],
+            &mut world
+        );
+
+        assert_eq!(13, world.get_state_by_pos(1, 0).unwrap().health);
+        assert_eq!(25, world.get_state_by_pos(0, 0).unwrap().health);
     }
 }
@@ -0,0 +1,78 @@
+use crate::types::*;
+
+impl World {
+    // Every living cell gets a tick older, pays for its age and dies after the lifespan,
+    // hardening wears off at the same time
+    pub(crate) fn age_cells(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if let Entity::Cell(genome_id) = self.entities[self.get_index(x, y)] {
+                    let (age, health) = {
+                        let state = self.cell_states.get_mut(genome_id);
+                        state.age += 1;
+                        state.defended_for = state.defended_for.saturating_sub(1);
+                        (state.age, state.health)
+                    };
+
+                    let settings = self.settings_at(x, y);
+                    let max_lifespan = settings.max_lifespan();
+                    let upkeep = (age as f64 * settings.age_upkeep()).round() as HealthType;
+
+                    if max_lifespan > 0 && age >= max_lifespan {
+                        self.update_health_ext(x, y, -health - 1, DeathCause::OldAge);
+                    } else if upkeep > 0 {
+                        self.update_health_ext(x, y, -upkeep, DeathCause::OldAge);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dies_of_old_age() {
+        let settings = SettingsBuilder::prod().with_max_lifespan(3).with_photosynthesis_adds(0).build();
+        let mut world = World::new(1, 1, settings);
+        let plant = Genome::new_plant();
+        let genes = *plant.genes;
+        world.set_cell(0, 0, plant);
+
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert_eq!(2, world.get_state_by_pos(0, 0).unwrap().age);
+
+        world.tick(&mut Processor::new());
+        assert_eq!(Entity::Corpse(20), *world.get_entity(0, 0));
+        assert_eq!(Some(&1), world.archive.get(&genes).unwrap().deaths.get(&DeathCause::OldAge));
+    }
+
+    #[test]
+    fn test_age_upkeep() {
+        let settings = SettingsBuilder::prod().with_photosynthesis_adds(0).with_age_upkeep(1.0).build();
+        let mut world = World::new(1, 1, settings);
+        world.set_cell(0, 0, Genome::new_plant());
+
+        // Pays 1, then 2, then 3
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert_eq!(4, world.get_state_by_pos(0, 0).unwrap().health);
+    }
+
+    #[test]
+    fn test_hardening_wears_off() {
+        let mut world = World::new(1, 1, SettingsBuilder::zero());
+        world.set_cell(0, 0, Genome::new_plant());
+        world.defend(0, 0, 2);
+
+        world.tick(&mut Processor::new());
+        assert_eq!(1, world.get_state_by_pos(0, 0).unwrap().defended_for);
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert_eq!(0, world.get_state_by_pos(0, 0).unwrap().defended_for);
+    }
+}
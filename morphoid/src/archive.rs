@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::types::*;
+
+impl DeathCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "starvation",
+            DeathCause::Attack => "attack",
+            DeathCause::Killed => "killed",
+            DeathCause::Catastrophe => "catastrophe",
+            DeathCause::OldAge => "old_age",
+        }
+    }
+}
+
+impl GenomeArchive {
+    pub fn new() -> GenomeArchive {
+        GenomeArchive { records: HashMap::new() }
+    }
+
+    pub fn born(&mut self, genome: &Genome, parent_genes: Option<&Genes>, tick: Tick) {
+        let record = self.records
+            .entry(Arc::clone(&genome.genes))
+            .or_insert_with(|| GenotypeRecord {
+                hash: genome.hash(),
+                genes: *genome.genes,
+                first_seen_tick: tick,
+                last_seen_tick: tick,
+                population: 0,
+                peak_population: 0,
+                total_offspring: 0,
+                deaths: HashMap::new()
+            });
+
+        record.population += 1;
+        record.last_seen_tick = tick;
+        if record.population > record.peak_population {
+            record.peak_population = record.population;
+        }
+
+        if let Some(parent) = parent_genes.and_then(|genes| self.records.get_mut(genes)) {
+            parent.total_offspring += 1;
+        }
+    }
+
+    pub fn died(&mut self, genes: &Genes, cause: DeathCause, tick: Tick) {
+        if let Some(record) = self.records.get_mut(genes) {
+            record.population -= 1;
+            record.last_seen_tick = tick;
+            *record.deaths.entry(cause).or_insert(0) += 1;
+        }
+    }
+
+    /// Genotypes still having living cells were seen at this tick
+    pub fn update(&mut self, genomes: &GenomeStorage, tick: Tick) {
+        for genes in genomes.shared.keys() {
+            if let Some(record) = self.records.get_mut(&**genes) {
+                record.last_seen_tick = tick;
+            }
+        }
+    }
+
+    pub fn get(&self, genes: &Genes) -> Option<&GenotypeRecord> {
+        self.records.get(genes)
+    }
+
+    /// The most successful genotypes by offspring, then by peak population
+    pub fn top(&self, count: usize) -> Vec<&GenotypeRecord> {
+        let mut result: Vec<&GenotypeRecord> = self.records.values().collect();
+        result.sort_by(|a, b| {
+            b.total_offspring.cmp(&a.total_offspring)
+                .then(b.peak_population.cmp(&a.peak_population))
+                .then(a.first_seen_tick.cmp(&b.first_seen_tick))
+        });
+        result.truncate(count);
+        result
+    }
+
+    /// Genotypes containing the pattern somewhere, None matches any gene
+    pub fn search(&self, pattern: &[Option<Gene>]) -> Vec<&GenotypeRecord> {
+        let mut result: Vec<&GenotypeRecord> = self.records
+            .values()
+            .filter(|record| GenomeArchive::matches(&record.genes, pattern))
+            .collect();
+        result.sort_by_key(|record| record.first_seen_tick);
+        result
+    }
+
+    fn matches(genes: &Genes, pattern: &[Option<Gene>]) -> bool {
+        if pattern.is_empty() {
+            return true;
+        }
+        genes.windows(pattern.len()).any(|window| {
+            window.iter()
+                .zip(pattern.iter())
+                .all(|(gene, expected)| expected.map_or(true, |expected| *gene == expected))
+        })
+    }
+}
+
+impl Default for GenomeArchive {
+    fn default() -> GenomeArchive {
+        GenomeArchive::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_born_and_died() {
+        let mut archive = GenomeArchive::new();
+        let plant = Genome::new_plant();
+        let child = plant.new_child();
+
+        archive.born(&plant, None, 1);
+        archive.born(&child, Some(&plant.genes), 2);
+        archive.died(&plant.genes, DeathCause::Attack, 3);
+        archive.died(&plant.genes, DeathCause::Attack, 5);
+
+        let record = archive.get(&plant.genes).unwrap();
+        assert_eq!(1, record.first_seen_tick);
+        assert_eq!(5, record.last_seen_tick);
+        assert_eq!(0, record.population);
+        assert_eq!(2, record.peak_population);
+        assert_eq!(1, record.total_offspring);
+        assert_eq!(Some(&2), record.deaths.get(&DeathCause::Attack));
+    }
+
+    #[test]
+    fn test_top_and_search() {
+        let mut archive = GenomeArchive::new();
+        let plant = Genome::new_plant();
+        let mut predator = Genome::new_predator();
+        predator.mutate(10, SENSE);
+        predator.mutate(12, MOVE);
+
+        archive.born(&plant, None, 0);
+        archive.born(&predator, None, 0);
+        archive.born(&predator.new_child(), Some(&predator.genes), 1);
+
+        let top = archive.top(1);
+        assert_eq!(1, top.len());
+        assert_eq!(predator.hash(), top[0].hash);
+
+        assert_eq!(1, archive.search(&[Some(SENSE), None, Some(MOVE)]).len());
+        assert_eq!(0, archive.search(&[Some(SENSE), Some(MOVE)]).len());
+        assert_eq!(2, archive.search(&[]).len());
+    }
+
+    #[test]
+    fn test_hash_collision() {
+        let mut archive = GenomeArchive::new();
+        let plant = Genome::new_plant();
+        // Same hash, different genes
+        let mut predator = Genome::new_predator();
+        predator.hash = plant.hash();
+
+        archive.born(&plant, None, 0);
+        archive.born(&predator, None, 0);
+        archive.died(&predator.genes, DeathCause::Attack, 1);
+
+        assert_eq!(2, archive.records.len());
+        assert_eq!(1, archive.get(&plant.genes).unwrap().population);
+        assert!(archive.get(&plant.genes).unwrap().deaths.is_empty());
+        assert_eq!(Some(&1), archive.get(&predator.genes).unwrap().deaths.get(&DeathCause::Attack));
+    }
+}
@@ -0,0 +1,117 @@
+use rand::{Rng, RngCore};
+
+use crate::types::*;
+
+impl BrainKind {
+    // Biggest change of a weight by one mutation, small steps keep the network working
+    const WEIGHT_STEP: Gene = 4;
+
+    pub fn by_name(name: &str) -> Option<BrainKind> {
+        match name {
+            "genes" => Some(BrainKind::Genes),
+            "neural" => Some(BrainKind::Neural),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrainKind::Genes => "genes",
+            BrainKind::Neural => "neural",
+        }
+    }
+
+    /// Brain for cells of a world with this kind, None is the gene machine of the processor
+    pub fn build(&self) -> Option<Box<dyn Brain>> {
+        match self {
+            BrainKind::Genes => None,
+            BrainKind::Neural => Some(Box::new(NeuralBrain::new())),
+        }
+    }
+
+    /// New value of the gene in a mutated child genome
+    pub fn mutate_gene(&self, gene: Gene, rng: &mut dyn RngCore) -> Gene {
+        match self {
+            // Any instruction could become any other
+            BrainKind::Genes => rng.gen_range(0, GENE_COUNT),
+            BrainKind::Neural => {
+                let step = rng.gen_range(1, BrainKind::WEIGHT_STEP + 1);
+                if rng.gen_bool(0.5) {
+                    gene.saturating_sub(step)
+                } else {
+                    (gene + step).min(GENE_COUNT - 1)
+                }
+            }
+        }
+    }
+}
+
+// The gene machine is the default brain
+impl Brain for Processor {
+    fn think(&mut self, x: Coords, y: Coords, genome_id: GenomeId, perceptor: &dyn Perceptor, settings: &Settings) -> Vec<Box<dyn Action>> {
+        self.execute(x, y, genome_id, perceptor, settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Third brain, every cell just gets healthier
+    struct Healer;
+
+    impl Brain for Healer {
+        fn think(&mut self, x: Coords, y: Coords, _genome_id: GenomeId, _perceptor: &dyn Perceptor, _settings: &Settings) -> Vec<Box<dyn Action>> {
+            vec![Box::new(UpdateHealthAction::new(x, y, 3))]
+        }
+
+        fn mutate_gene(&self, _gene: Gene, _rng: &mut dyn RngCore) -> Gene {
+            PHOTOSYNTHESIS
+        }
+    }
+
+    #[test]
+    fn integration_custom_brain() {
+        let mut processor = Processor::new();
+        let mut world = World::new(1, 1, SettingsBuilder::zero());
+        world.set_brain(Box::new(Healer));
+        let plant = Genome::new_plant();
+        let hash = plant.id();
+        world.set_cell(0, 0, plant);
+
+        world.tick(&mut processor);
+        assert_eq!(SettingsBuilder::zero().initial_cell_health() + 3, world.get_state(hash).health);
+        assert!(processor.genome_states.is_empty());
+        assert!(world.brain.is_some());
+
+        // Going back to the gene machine through settings
+        let mut neural = SettingsBuilder::zero();
+        neural.brain = BrainKind::Neural;
+        world.update_settings(neural);
+        assert!(world.brain.is_some());
+        world.update_settings(SettingsBuilder::zero());
+        assert!(world.brain.is_none());
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(Some(BrainKind::Neural), BrainKind::by_name(BrainKind::Neural.name()));
+        assert_eq!(None, BrainKind::by_name("lizard"));
+    }
+
+    #[test]
+    fn test_neural_mutation_is_small() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let gene = BrainKind::Neural.mutate_gene(PHOTOSYNTHESIS, &mut rng);
+            assert_ne!(PHOTOSYNTHESIS, gene);
+            assert!(gene + BrainKind::WEIGHT_STEP >= PHOTOSYNTHESIS);
+            assert!(gene <= PHOTOSYNTHESIS + BrainKind::WEIGHT_STEP);
+        }
+
+        for _ in 0..100 {
+            assert!(BrainKind::Neural.mutate_gene(GENE_COUNT - 1, &mut rng) < GENE_COUNT);
+            assert!(BrainKind::Genes.mutate_gene(0, &mut rng) < GENE_COUNT);
+        }
+    }
+}
@@ -2,10 +2,10 @@ use crate::types::*;
 
 impl CellState {
     pub fn new(initial_health: HealthType, direction: Direction) -> CellState {
-        CellState { health: initial_health, direction: direction }
+        CellState { health: initial_health, direction: direction, age: 0, defended_for: 0, registers: [0; REGISTERS] }
     }
 
     pub fn default() -> CellState {
-        CellState { health: 10, direction: Direction::North }
+        CellState { health: 10, direction: Direction::North, age: 0, defended_for: 0, registers: [0; REGISTERS] }
     }
 }
@@ -38,10 +38,121 @@ impl Direction {
     }
 }
 
+impl Neighbourhood {
+    const VON_NEUMANN: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    const HEX: [Direction; 6] = [
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    pub fn by_name(name: &str) -> Option<Neighbourhood> {
+        match name {
+            "von_neumann" => Some(Neighbourhood::VonNeumann),
+            "moore" => Some(Neighbourhood::Moore),
+            "hex" => Some(Neighbourhood::Hex),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Neighbourhood::VonNeumann => "von_neumann",
+            Neighbourhood::Moore => "moore",
+            Neighbourhood::Hex => "hex",
+        }
+    }
+
+    /// Clockwise, starting from the north
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            Neighbourhood::VonNeumann => &Neighbourhood::VON_NEUMANN,
+            Neighbourhood::Moore => &Direction::DIRECTIONS,
+            Neighbourhood::Hex => &Neighbourhood::HEX,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.directions().len()
+    }
+
+    pub fn by_value(&self, value: Gene) -> Direction {
+        self.directions()[value % self.size()]
+    }
+
+    /// Direction which is not part of the neighbourhood becomes the next one clockwise
+    pub fn normalize(&self, direction: Direction) -> Direction {
+        let directions = self.directions();
+        (0..Direction::SIZE)
+            .map(|value| direction.rotate(value))
+            .find(|candidate| directions.contains(candidate))
+            .unwrap()
+    }
+
+    /// value is Gene because it is passed from genome
+    pub fn rotate(&self, direction: Direction, value: Gene) -> Direction {
+        let directions = self.directions();
+        let normalized = self.normalize(direction);
+        let position = directions.iter().position(|candidate| *candidate == normalized).unwrap();
+        directions[(position + value) % directions.len()]
+    }
+
+    /// Shift depends on the row only for hex grid
+    pub fn shift(&self, direction: Direction, y: Coords) -> (Coords, Coords) {
+        let direction = self.normalize(direction);
+        match self {
+            Neighbourhood::Hex => {
+                let odd_row = y.rem_euclid(2) == 1;
+                match direction {
+                    Direction::NorthEast => (if odd_row { 1 } else { 0 }, -1),
+                    Direction::NorthWest => (if odd_row { 0 } else { -1 }, -1),
+                    Direction::SouthEast => (if odd_row { 1 } else { 0 }, 1),
+                    Direction::SouthWest => (if odd_row { 0 } else { -1 }, 1),
+                    other => other.shift()
+                }
+            },
+            _ => direction.shift()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_von_neumann() {
+        let neighbourhood = Neighbourhood::VonNeumann;
+        assert_eq!(Direction::East, neighbourhood.rotate(Direction::North, 1));
+        assert_eq!(Direction::North, neighbourhood.rotate(Direction::West, 1));
+        assert_eq!(Direction::South, neighbourhood.rotate(Direction::NorthEast, 1));
+        assert_eq!((0, 1), neighbourhood.shift(Direction::South, 0));
+        assert_eq!(Direction::West, neighbourhood.by_value(7));
+    }
+
+    #[test]
+    fn test_hex() {
+        let neighbourhood = Neighbourhood::Hex;
+        assert_eq!(Direction::NorthEast, neighbourhood.normalize(Direction::North));
+        assert_eq!(Direction::NorthWest, neighbourhood.rotate(Direction::NorthEast, 5));
+        assert_eq!(Direction::NorthEast, neighbourhood.rotate(Direction::NorthWest, 7));
+
+        assert_eq!((0, -1), neighbourhood.shift(Direction::NorthEast, 0));
+        assert_eq!((1, -1), neighbourhood.shift(Direction::NorthEast, 1));
+        assert_eq!((-1, 1), neighbourhood.shift(Direction::SouthWest, 2));
+        assert_eq!((0, 1), neighbourhood.shift(Direction::SouthWest, -1));
+        assert_eq!((-1, 0), neighbourhood.shift(Direction::West, 1));
+    }
+
     #[test]
     fn test_rotate() {
         assert_eq!(Direction::NorthEast, Direction::North.rotate(1));
@@ -0,0 +1,226 @@
+use rand::Rng;
+
+use crate::types::*;
+
+impl EventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::Wipe { .. } => "wipe",
+            EventKind::Cull { .. } => "cull",
+            EventKind::MutationSpike { .. } => "mutation_spike",
+            EventKind::FlipLight => "flip_light",
+        }
+    }
+
+    /// Shares and probabilities are within 0..1, a spike lasts at least one tick
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            EventKind::Cull { share } if !(*share >= 0.0 && *share <= 1.0) =>
+                Err(format!("Cull share should be within 0..=1, not {}", share)),
+            EventKind::MutationSpike { probability, .. } if !(*probability >= 0.0 && *probability <= 1.0) =>
+                Err(format!("Mutation probability should be within 0..=1, not {}", probability)),
+            EventKind::MutationSpike { duration: 0, .. } => Err("Mutation spike should last at least one tick".to_string()),
+            _ => Ok(())
+        }
+    }
+}
+
+impl EventQueue {
+    pub fn new() -> EventQueue {
+        EventQueue { pending: Vec::new(), log: Vec::new(), spikes: Vec::new() }
+    }
+
+    pub fn schedule(&mut self, tick: Tick, kind: EventKind) -> Result<(), String> {
+        kind.check()?;
+        let position = self.pending
+            .iter()
+            .position(|(pending_tick, _)| *pending_tick > tick)
+            .unwrap_or(self.pending.len());
+        self.pending.insert(position, (tick, kind));
+        Ok(())
+    }
+
+    /// Probability of the latest mutation spike going on, it wins over the settings
+    pub fn spike_probability(&self) -> Option<f64> {
+        self.spikes.last().map(|(_, probability)| *probability)
+    }
+
+    // Events which time has come, in order of scheduling
+    fn take_due(&mut self, tick: Tick) -> Vec<EventKind> {
+        let count = self.pending
+            .iter()
+            .take_while(|(pending_tick, _)| *pending_tick <= tick)
+            .count();
+        self.pending.drain(..count).map(|(_, kind)| kind).collect()
+    }
+}
+
+impl World {
+    /// Applies the event right away, returns its log record
+    pub fn trigger_event(&mut self, kind: EventKind) -> Result<EventRecord, String> {
+        kind.check()?;
+        let mut rng = rand::thread_rng();
+
+        let killed = match kind {
+            EventKind::Wipe { width, height } => {
+                let left = rng.gen_range(0, (self.width - width).max(0) + 1);
+                let top = rng.gen_range(0, (self.height - height).max(0) + 1);
+                self.kill_cells_where(|x, y| x >= left && x < left + width && y >= top && y < top + height)
+            },
+            EventKind::Cull { share } => self.kill_cells_where(|_, _| rng.gen_bool(share)),
+            EventKind::MutationSpike { probability, duration } => {
+                self.events.spikes.push((self.tick_no + duration, probability));
+                0
+            },
+            EventKind::FlipLight => {
+                self.light_flipped = !self.light_flipped;
+                0
+            }
+        };
+
+        let record = EventRecord { tick: self.tick_no, kind, killed };
+        self.events.log.push(record.clone());
+        Ok(record)
+    }
+
+    // Spike going on wins over the settings of the square
+    pub(crate) fn mutation_probability(&self, settings: &Settings) -> f64 {
+        self.events.spike_probability().unwrap_or(settings.mutation_probability())
+    }
+
+    pub(crate) fn run_pending_events(&mut self) {
+        let tick = self.tick_no;
+        self.events.spikes.retain(|(end_tick, _)| *end_tick > tick);
+        for kind in self.events.take_due(tick) {
+            // Could not fail, events are checked when scheduled
+            let _ = self.trigger_event(kind);
+        }
+    }
+
+    // Cells become fresh corpses, returns number of killed cells
+    fn kill_cells_where<F>(&mut self, mut condition: F) -> usize where F: FnMut(Coords, Coords) -> bool {
+        let mut killed = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if let Entity::Cell(_) = self.get_entity(x, y) {
+                    if condition(x, y) {
+                        let corpse = Entity::Corpse(self.settings_at(x, y).corpse_initial());
+                        self.replace_entity(x, y, corpse, None, None, DeathCause::Catastrophe);
+                        killed += 1;
+                    }
+                }
+            }
+        }
+        killed
+    }
+}
+
+impl Default for EventQueue {
+    fn default() -> EventQueue {
+        EventQueue::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated_world() -> World {
+        let mut world = World::new(3, 2, SettingsBuilder::zero());
+        for x in 0..3 {
+            world.set_cell(x, 0, Genome::new_plant());
+        }
+        world
+    }
+
+    #[test]
+    fn test_wipe_and_cull() {
+        let mut world = populated_world();
+        let record = world.trigger_event(EventKind::Cull { share: 0.0 }).unwrap();
+        assert_eq!(0, record.killed);
+
+        let record = world.trigger_event(EventKind::Wipe { width: 3, height: 2 }).unwrap();
+        assert_eq!(3, record.killed);
+        assert_eq!(Entity::Corpse(0), *world.get_entity(1, 0));
+        assert_eq!(2, world.events.log.len());
+
+        let mut world = populated_world();
+        assert_eq!(3, world.trigger_event(EventKind::Cull { share: 1.0 }).unwrap().killed);
+    }
+
+    #[test]
+    fn test_invalid_events() {
+        let mut world = populated_world();
+        assert!(world.trigger_event(EventKind::MutationSpike { probability: 2.0, duration: 5 }).is_err());
+        assert!(world.trigger_event(EventKind::MutationSpike { probability: 0.5, duration: 0 }).is_err());
+        assert!(world.trigger_event(EventKind::Cull { share: f64::NAN }).is_err());
+        assert!(world.events.schedule(1, EventKind::MutationSpike { probability: -0.1, duration: 5 }).is_err());
+
+        assert!(world.events.log.is_empty());
+        assert!(world.events.pending.is_empty());
+        assert!(world.events.spikes.is_empty());
+    }
+
+    #[test]
+    fn test_flip_light() {
+        let settings = SettingsBuilder::prod().with_light_depth_falloff(1.0).build();
+        let mut world = World::new(1, 2, settings);
+
+        world.trigger_event(EventKind::FlipLight).unwrap();
+        assert_eq!(0.0, world.light(0, 0));
+        assert_eq!(1.0, world.light(0, 1));
+    }
+
+    #[test]
+    fn integration_test_scheduled_mutation_spike() {
+        let mut world = World::new(1, 1, SettingsBuilder::zero());
+        world.events.schedule(2, EventKind::MutationSpike { probability: 0.5, duration: 2 }).unwrap();
+        world.events.schedule(1, EventKind::FlipLight).unwrap();
+
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert!(world.light_flipped);
+        assert_eq!(1, world.events.pending.len());
+
+        world.tick(&mut Processor::new());
+        assert_eq!(0.5, world.mutation_probability(world.settings_at(0, 0)));
+        assert_eq!(0.0, world.get_settings().mutation_probability());
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert_eq!(0.0, world.mutation_probability(world.settings_at(0, 0)));
+
+        let names: Vec<&str> = world.events.log.iter().map(|record| record.kind.name()).collect();
+        assert_eq!(vec!["flip_light", "mutation_spike"], names);
+        assert_eq!(2, world.events.log[1].tick);
+    }
+
+    #[test]
+    fn integration_test_overlapping_mutation_spikes() {
+        let mut world = World::new(1, 1, SettingsBuilder::zero());
+        // First one ends while the second is going on
+        world.events.schedule(1, EventKind::MutationSpike { probability: 0.5, duration: 2 }).unwrap();
+        world.events.schedule(2, EventKind::MutationSpike { probability: 0.7, duration: 3 }).unwrap();
+
+        let mut probabilities = Vec::new();
+        for _ in 0..7 {
+            world.tick(&mut Processor::new());
+            probabilities.push(world.mutation_probability(world.settings_at(0, 0)));
+        }
+        assert_eq!(vec![0.0, 0.5, 0.7, 0.7, 0.7, 0.0, 0.0], probabilities);
+        assert!(world.events.spikes.is_empty());
+    }
+
+    #[test]
+    fn integration_test_settings_changed_during_spike() {
+        let mut world = World::new(1, 1, SettingsBuilder::zero());
+        world.trigger_event(EventKind::MutationSpike { probability: 0.5, duration: 2 }).unwrap();
+        world.update_settings(SettingsBuilder::prod().with_mutation_probability(0.3).build());
+
+        world.tick(&mut Processor::new());
+        assert_eq!(0.5, world.mutation_probability(world.settings_at(0, 0)));
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert_eq!(0.3, world.mutation_probability(world.settings_at(0, 0)));
+        assert_eq!(0.3, world.get_settings().mutation_probability());
+    }
+}
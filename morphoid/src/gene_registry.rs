@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use crate::types::*;
+
+impl GeneRegistry {
+    pub fn new() -> GeneRegistry {
+        GeneRegistry { handlers: HashMap::new() }
+    }
+
+    /// Opcode of the handler must be free, built-in genes could not be replaced
+    pub fn register(&mut self, handler: Box<dyn GeneHandler>) -> Result<(), String> {
+        let opcode = handler.opcode();
+        if opcode >= GENE_COUNT {
+            return Err(format!("Opcode {} is out of range, genes are below {}", opcode, GENE_COUNT));
+        }
+        if KNOWN_GENES.contains(&opcode) || self.handlers.contains_key(&opcode) {
+            return Err(format!("Opcode {} is already taken", opcode));
+        }
+        self.handlers.insert(opcode, handler);
+        Ok(())
+    }
+
+    pub fn get(&self, gene: Gene) -> Option<&dyn GeneHandler> {
+        self.handlers.get(&gene).map(|handler| handler.as_ref())
+    }
+
+    /// True if the gene does something, everything else is a goto
+    pub fn is_known(&self, gene: Gene) -> bool {
+        KNOWN_GENES.contains(&gene) || self.handlers.contains_key(&gene)
+    }
+
+    /// Built-in genes first, then the registered ones by opcode
+    pub fn known_genes(&self) -> Vec<Gene> {
+        let mut custom: Vec<Gene> = self.handlers.keys().cloned().collect();
+        custom.sort();
+
+        let mut genes = KNOWN_GENES.to_vec();
+        genes.append(&mut custom);
+        genes
+    }
+
+    /// Number of genes after this one read as operands
+    pub fn operands(&self, gene: Gene) -> usize {
+        match gene {
+            TURN | EMIT | SMELL | ATTACK_TOWARDS | MOVE_TOWARDS | REPRODUCE_TOWARDS | SENSE_TOWARDS => 1,
+            STORE_SENSE | INCREMENT | DECREMENT | IF_ZERO => 1,
+            DEFILE | SENSE | MOVE | ATTACK | REPRODUCE | PHOTOSYNTHESIS | ABSORB | DEFEND | LOOK => 0,
+            _ => self.get(gene).map_or(0, |handler| handler.operands())
+        }
+    }
+
+    pub fn category(&self, gene: Gene) -> GeneCategory {
+        match gene {
+            REPRODUCE => GeneCategory::Reproduces,
+            ATTACK => GeneCategory::Attacks,
+            PHOTOSYNTHESIS => GeneCategory::Photosynthesis,
+            DEFILE => GeneCategory::Defiles,
+            _ => self.get(gene).map_or(GeneCategory::Other, |handler| handler.category())
+        }
+    }
+}
+
+impl Default for GeneRegistry {
+    fn default() -> GeneRegistry {
+        GeneRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Heals the cell by the operand
+    struct Heal;
+
+    impl GeneHandler for Heal {
+        fn opcode(&self) -> Gene { 50 }
+        fn operands(&self) -> usize { 1 }
+        fn cost(&self, settings: &Settings) -> HealthType { settings.sense_cost() }
+        fn category(&self) -> GeneCategory { GeneCategory::Photosynthesis }
+
+        fn actions(&self, x: Coords, y: Coords, _genome_id: GenomeId, operands: &[Gene],
+                   _perceptor: &dyn Perceptor, _settings: &Settings) -> Vec<Box<dyn Action>> {
+            vec![Box::new(UpdateHealthAction::new(x, y, operands[0] as HealthType))]
+        }
+    }
+
+    struct Opcode(Gene);
+
+    impl GeneHandler for Opcode {
+        fn opcode(&self) -> Gene { self.0 }
+
+        fn actions(&self, _x: Coords, _y: Coords, _genome_id: GenomeId, _operands: &[Gene],
+                   _perceptor: &dyn Perceptor, _settings: &Settings) -> Vec<Box<dyn Action>> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = GeneRegistry::new();
+        assert!(registry.register(Box::new(Heal)).is_ok());
+        assert!(registry.register(Box::new(Opcode(50))).is_err());
+        assert!(registry.register(Box::new(Opcode(ATTACK))).is_err());
+        assert!(registry.register(Box::new(Opcode(GENE_COUNT))).is_err());
+        assert!(registry.register(Box::new(Opcode(3))).is_ok());
+
+        assert!(registry.is_known(50));
+        assert!(!registry.is_known(51));
+        assert_eq!(KNOWN_GENES.len() + 2, registry.known_genes().len());
+        assert_eq!(Some(&50), registry.known_genes().last());
+        assert_eq!(1, registry.operands(50));
+        assert_eq!(GeneCategory::Photosynthesis, registry.category(50));
+        assert_eq!(GeneCategory::Other, registry.category(3));
+    }
+
+    #[test]
+    fn test_builtin_operands() {
+        let registry = GeneRegistry::new();
+        let expected = [
+            (DEFILE, 0), (SENSE, 0), (TURN, 1), (MOVE, 0), (ATTACK, 0), (REPRODUCE, 0),
+            (PHOTOSYNTHESIS, 0), (ABSORB, 0), (EMIT, 1), (SMELL, 1), (DEFEND, 0),
+            (ATTACK_TOWARDS, 1), (MOVE_TOWARDS, 1), (REPRODUCE_TOWARDS, 1), (SENSE_TOWARDS, 1),
+            (LOOK, 0), (STORE_SENSE, 1), (INCREMENT, 1), (DECREMENT, 1), (IF_ZERO, 1),
+        ];
+        assert_eq!(KNOWN_GENES.len(), expected.len());
+        for (gene, operands) in expected.iter() {
+            assert!(KNOWN_GENES.contains(gene));
+            assert_eq!(*operands, registry.operands(*gene), "operands of gene {}", gene);
+        }
+    }
+
+    #[test]
+    fn integration_custom_gene() {
+        let settings = SettingsBuilder::prod()
+            .with_photosynthesis_adds(0)
+            .with_sense_cost(-1)
+            .build();
+        let mut processor = Processor::new();
+        let mut world = World::new(1, 1, settings);
+        world.register_gene(Box::new(Heal)).unwrap();
+
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, 50);
+        genome.mutate(1, 7);
+        let hash = genome.id();
+        world.set_cell(0, 0, genome);
+
+        world.tick(&mut processor);
+        assert_eq!(2, processor.genome_states[&hash].current_gene);
+        assert_eq!(Settings::prod().initial_cell_health() + 6, world.get_state(hash).health);
+        assert_eq!(GENOME_LENGTH - 1, world.genomes.describe(hash).unwrap().photosynthesis);
+    }
+}
@@ -1,7 +1,11 @@
 use std::fmt;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use itertools::Itertools;
+use rand::Rng;
 
 use crate::types::*;
 
@@ -12,8 +16,60 @@ impl Genome {
         HASH_COUNTER.fetch_add(1, Ordering::SeqCst) as GenomeId
     }
 
+    /// Content hash, genomes with the same genes share it
+    pub fn hash_of(genes: &Genes) -> GenomeHash {
+        let mut hasher = DefaultHasher::new();
+        genes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn from_genes(genes: Genes) -> Genome {
+        Genome {
+            id: Genome::new_id(),
+            hash: Genome::hash_of(&genes),
+            genes: Arc::new(genes),
+            parent_id: None,
+            birth_tick: 0,
+            mutations: Vec::new()
+        }
+    }
+
     pub fn new_plant() -> Genome {
-        Genome {id: Genome::new_id(), genes: [PHOTOSYNTHESIS; GENOME_LENGTH]}
+        Genome::from_genes([PHOTOSYNTHESIS; GENOME_LENGTH])
+    }
+
+    /// Known genes at random, operands follow the genes which need them
+    pub fn new_random<R: Rng>(rng: &mut R) -> Genome {
+        Genome::new_random_with(rng, &GeneRegistry::new())
+    }
+
+    /// Same as new_random, registered genes are used too
+    pub fn new_random_with<R: Rng>(rng: &mut R, registry: &GeneRegistry) -> Genome {
+        let known_genes = registry.known_genes();
+        let mut genes = [PHOTOSYNTHESIS; GENOME_LENGTH];
+        let mut i = 0;
+        while i < GENOME_LENGTH - 4 {
+            let gene = known_genes[rng.gen_range(0, known_genes.len())];
+            genes[i] = gene;
+            i += 1;
+
+            // Custom genes could have more operands than there are genes left
+            for _ in 0..registry.operands(gene) + Genome::branch_padding(gene) {
+                if i < GENOME_LENGTH {
+                    genes[i] = rng.gen_range(0, GENOME_LENGTH);
+                    i += 1;
+                }
+            }
+        }
+        Genome::from_genes(genes)
+    }
+
+    // Random genes after the conditionals, so that their branches lead somewhere random
+    fn branch_padding(gene: Gene) -> usize {
+        match gene {
+            SENSE | SENSE_TOWARDS => 2,
+            _ => 0
+        }
     }
 
     pub fn new_reproducing_plant() -> Genome {
@@ -21,33 +77,64 @@ impl Genome {
         for i in 0..GENOME_LENGTH {
             if i % 2 != 0 { genes[i] = REPRODUCE; }
         }
-        Genome {id: Genome::new_id(), genes: genes}
+        Genome::from_genes(genes)
     }
 
     pub fn new_predator() -> Genome {
-        Genome {id: Genome::new_id(), genes: [ATTACK; GENOME_LENGTH]}
+        Genome::from_genes([ATTACK; GENOME_LENGTH])
     }
 
     pub fn new_yeast() -> Genome {
-        Genome {id: Genome::new_id(), genes: [REPRODUCE; GENOME_LENGTH]}
+        Genome::from_genes([REPRODUCE; GENOME_LENGTH])
     }
 
     pub fn new_defiler() -> Genome {
-        Genome {id: Genome::new_id(), genes: [DEFILE; GENOME_LENGTH]}
+        Genome::from_genes([DEFILE; GENOME_LENGTH])
     }
 
     pub fn id(&self) -> GenomeId {
         self.id
     }
 
+    pub fn hash(&self) -> GenomeHash {
+        self.hash
+    }
+
+    pub fn parent_id(&self) -> Option<GenomeId> {
+        self.parent_id
+    }
+
+    pub fn birth_tick(&self) -> Tick {
+        self.birth_tick
+    }
+
+    /// Genes are copied if they are shared with other genomes
     pub fn mutate(&mut self, index: usize, new_value: Gene) {
-        self.genes[index] = new_value;
+        Arc::make_mut(&mut self.genes)[index] = new_value;
+        self.hash = Genome::hash_of(&self.genes);
     }
 
-    pub fn clone(&self) -> Genome {
-        let mut new_genome = Genome {id: Genome::new_id(), genes: [PHOTOSYNTHESIS; GENOME_LENGTH]};
-        new_genome.genes.copy_from_slice(&self.genes[..]);
-        new_genome
+    /// Same as mutate but keeps the change in the list of mutations
+    /// so it could be traced in lineage later
+    pub fn apply_mutation(&mut self, index: usize, new_value: Gene) {
+        self.mutations.push(Mutation {
+            index,
+            old_gene: self.genes[index],
+            new_gene: new_value
+        });
+        self.mutate(index, new_value);
+    }
+
+    /// Shares the genes with a new id, remembering where it came from
+    pub fn new_child(&self) -> Genome {
+        Genome {
+            id: Genome::new_id(),
+            hash: self.hash,
+            genes: Arc::clone(&self.genes),
+            parent_id: Some(self.id),
+            birth_tick: 0,
+            mutations: Vec::new()
+        }
     }
 }
 
@@ -59,10 +146,7 @@ impl fmt::Debug for Genome {
 
 impl PartialEq for Genome {
     fn eq(&self, other: &Self) -> bool {
-        //self.genes == other.genes //TODO: why it does not work?
-        self.genes.iter()
-            .zip(other.genes.iter())
-            .find(|(a,b)| a != b) == None
+        self.hash == other.hash && self.genes == other.genes
     }
 }
 
@@ -75,7 +159,7 @@ mod tests {
         let genome1 = Genome::new_plant();
         let genome2 = Genome::new_plant();
         let mut genome3 = Genome::new_plant();
-        genome3.genes[0] = 22;
+        genome3.mutate(0, 22);
 
         assert_eq!(genome1, genome2);
         assert_ne!(genome2, genome3);
@@ -93,8 +177,9 @@ mod tests {
     fn clone() {
         let genome1 = Genome::new_plant();
         let genome2 = genome1.clone();
-        assert_ne!(genome1.id(), genome2.id());
+        assert_eq!(genome1.id(), genome2.id());
         assert_eq!(genome1, genome2);
+        assert!(Arc::ptr_eq(&genome1.genes, &genome2.genes));
     }
 
     #[test]
@@ -104,6 +189,25 @@ mod tests {
         assert_eq!(genome1, genome2);
         genome2.mutate(0, REPRODUCE);
         assert_ne!(genome1, genome2);
+        assert_ne!(genome1.hash(), genome2.hash());
+        assert_eq!(PHOTOSYNTHESIS, genome1.genes[0]);
+    }
+
+    #[test]
+    fn new_child() {
+        let parent = Genome::new_plant();
+        let mut child = parent.new_child();
+        assert_ne!(parent.id(), child.id());
+        assert!(Arc::ptr_eq(&parent.genes, &child.genes));
+        assert_eq!(Some(parent.id()), child.parent_id());
+        assert!(child.mutations.is_empty());
+
+        child.apply_mutation(3, ATTACK);
+        assert_eq!(ATTACK, child.genes[3]);
+        assert_eq!(
+            vec![Mutation { index: 3, old_gene: PHOTOSYNTHESIS, new_gene: ATTACK }],
+            child.mutations
+        );
     }
 
 
@@ -2,18 +2,23 @@ use crate::types::*;
 
 impl GenomeDesc {
     pub fn build_from(genome: &Genome) -> GenomeDesc {
+        GenomeDesc::build_with(genome, &GeneRegistry::new())
+    }
+
+    /// Registered genes are counted by their category
+    pub fn build_with(genome: &Genome, registry: &GeneRegistry) -> GenomeDesc {
         let mut reproduces: usize = 0;
         let mut attacks: usize = 0;
         let mut photosynthesis: usize = 0;
         let mut defiles: usize = 0;
 
         for gene in genome.genes.iter() {
-            match *gene {
-                ATTACK => attacks += 1,
-                REPRODUCE => reproduces += 1,
-                PHOTOSYNTHESIS => photosynthesis += 1,
-                DEFILE => defiles += 1,
-                _ => {}
+            match registry.category(*gene) {
+                GeneCategory::Attacks => attacks += 1,
+                GeneCategory::Reproduces => reproduces += 1,
+                GeneCategory::Photosynthesis => photosynthesis += 1,
+                GeneCategory::Defiles => defiles += 1,
+                GeneCategory::Other => {}
             }
         }
 
@@ -24,6 +29,17 @@ impl GenomeDesc {
             defiles: defiles
         }
     }
+
+    /// Rough category of the genome, the one it is displayed with
+    pub fn icon(&self) -> char {
+        match self {
+            x if x.reproduces > x.attacks + x.photosynthesis => '*',
+            x if x.attacks > x.photosynthesis + x.defiles  => 'x',
+            x if x.photosynthesis > x.attacks + x.defiles => 'o',
+            x if x.defiles > x.attacks + x.photosynthesis => '@',
+            _ => '.'
+        }
+    }
 }
 
 #[cfg(test)]
@@ -40,4 +56,18 @@ mod tests {
         assert_eq!(0, desc2.photosynthesis);
         assert_eq!(GENOME_LENGTH, desc2.attacks);
     }
+
+    #[test]
+    fn test_icon() {
+        assert_eq!('o', GenomeDesc::build_from(&Genome::new_plant()).icon());
+        assert_eq!('x', GenomeDesc::build_from(&Genome::new_predator()).icon());
+        assert_eq!('*', GenomeDesc::build_from(&Genome::new_yeast()).icon());
+        assert_eq!('@', GenomeDesc::build_from(&Genome::new_defiler()).icon());
+
+        let mut mixed = Genome::new_plant();
+        for i in 0..GENOME_LENGTH / 2 {
+            mixed.mutate(i, ATTACK);
+        }
+        assert_eq!('.', GenomeDesc::build_from(&mixed).icon());
+    }
 }
@@ -1,26 +1,53 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+
 use crate::types::*;
 
 impl GenomeStorage {
     pub fn new() -> GenomeStorage {
         GenomeStorage {
             genomes: HashMap::new(),
-            descriptors: HashMap::new()
+            shared: HashMap::new()
         }
     }
 
+    /// Genes of the genome are replaced with shared ones if there is such content already
     pub fn put(&mut self, genome:Genome) -> GenomeId {
+        self.put_with(genome, &GeneRegistry::new())
+    }
+
+    /// Same as put, registered genes are described by their category
+    pub fn put_with(&mut self, mut genome:Genome, registry: &GeneRegistry) -> GenomeId {
         let id = genome.id();
 
-        self.descriptors.insert(id, GenomeDesc::build_from(&genome));
-        self.genomes.insert(id, genome);
+        let shared = self.shared
+            .entry(Arc::clone(&genome.genes))
+            .or_insert_with(|| SharedGenes {
+                genes: Arc::clone(&genome.genes),
+                hash: genome.hash(),
+                desc: GenomeDesc::build_with(&genome, registry),
+                cells: 0
+            });
+        shared.cells += 1;
+        genome.genes = Arc::clone(&shared.genes);
 
+        self.genomes.insert(id, genome);
         id
     }
 
     pub fn remove(&mut self, id: GenomeId) {
-        self.genomes.remove(&id);
-        self.descriptors.remove(&id);
+        if let Some(genome) = self.genomes.remove(&id) {
+            let unused = match self.shared.get_mut(&*genome.genes) {
+                Some(shared) => {
+                    shared.cells -= 1;
+                    shared.cells == 0
+                },
+                None => false
+            };
+            if unused {
+                self.shared.remove(&*genome.genes);
+            }
+        }
     }
 
     pub fn get(&self, id: GenomeId) -> Option<&Genome> {
@@ -28,7 +55,26 @@ impl GenomeStorage {
     }
 
     pub fn describe(&self, id: GenomeId) -> Option<&GenomeDesc> {
-        self.descriptors.get(&id)
+        self.genomes
+            .get(&id)
+            .and_then(|genome| self.shared.get(&*genome.genes))
+            .map(|shared| &shared.desc)
+    }
+
+    /// Number of living cells with exactly these genes
+    pub fn count_by_genes(&self, genes: &Genes) -> usize {
+        self.shared.get(genes).map_or(0, |shared| shared.cells)
+    }
+
+    /// Number of living cells sharing genes with this one, including itself
+    pub fn count_same(&self, id: GenomeId) -> usize {
+        self.genomes
+            .get(&id)
+            .map_or(0, |genome| self.count_by_genes(&genome.genes))
+    }
+
+    pub fn distinct_count(&self) -> usize {
+        self.shared.len()
     }
 }
 
@@ -59,4 +105,47 @@ mod tests {
         assert_eq!(0, desc.attacks);
         assert_eq!(GENOME_LENGTH, desc.photosynthesis);
     }
+
+    #[test]
+    fn test_interning() {
+        let mut storage = GenomeStorage::new();
+        let plant = Genome::new_plant();
+        let same_plant = Genome::new_plant();
+        let mut other_plant = plant.new_child();
+        other_plant.mutate(0, ATTACK);
+
+        let id = storage.put(plant);
+        let same_id = storage.put(same_plant);
+        let other_id = storage.put(other_plant);
+
+        assert_eq!(2, storage.distinct_count());
+        assert_eq!(2, storage.count_same(id));
+        assert_eq!(1, storage.count_same(other_id));
+        assert!(Arc::ptr_eq(&storage.get(id).unwrap().genes, &storage.get(same_id).unwrap().genes));
+
+        storage.remove(id);
+        assert_eq!(1, storage.count_same(same_id));
+        assert!(storage.describe(same_id).is_some());
+
+        storage.remove(same_id);
+        assert_eq!(1, storage.distinct_count());
+        assert_eq!(0, storage.count_same(same_id));
+    }
+
+    #[test]
+    fn test_hash_collision() {
+        let mut storage = GenomeStorage::new();
+        let plant = Genome::new_plant();
+        // Same hash, different genes
+        let mut predator = Genome::new_predator();
+        predator.hash = plant.hash();
+
+        let plant_id = storage.put(plant);
+        let predator_id = storage.put(predator);
+
+        assert_eq!(2, storage.distinct_count());
+        assert_eq!(ATTACK, storage.get(predator_id).unwrap().genes[0]);
+        assert_eq!(GENOME_LENGTH, storage.describe(predator_id).unwrap().attacks);
+        assert_eq!(1, storage.count_same(plant_id));
+    }
 }
@@ -0,0 +1,83 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::types::*;
+
+impl World {
+    pub fn population(&self) -> usize {
+        self.genomes.genomes.len()
+    }
+
+    /// Spawns random organisms on empty squares, returns how many were placed
+    pub fn spawn_random(&mut self, count: usize) -> usize {
+        let mut rng = rand::thread_rng();
+
+        let mut empty: Vec<(Coords, Coords)> = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if *self.get_entity(x, y) == Entity::Nothing {
+                    empty.push((x, y));
+                }
+            }
+        }
+
+        let chosen: Vec<(Coords, Coords)> = empty.choose_multiple(&mut rng, count).cloned().collect();
+        for (x, y) in chosen.iter() {
+            let genome = Genome::new_random_with(&mut rng, &self.gene_registry);
+            let neighbourhood = self.settings.neighbourhood();
+            let direction = neighbourhood.by_value(rng.gen_range(0, neighbourhood.size()));
+            self.set_cell_ext(*x, *y, genome, direction);
+        }
+        chosen.len()
+    }
+
+    // Immigrants every tick, and a reseed if the population has fallen below the floor
+    pub(crate) fn immigrate(&mut self) {
+        let mut count = self.settings.immigration_rate();
+
+        let floor = self.settings.reseed_floor();
+        let population = self.population();
+        if population < floor {
+            count = count.max(floor - population);
+        }
+
+        if count > 0 {
+            self.spawn_random(count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_only_on_empty_squares() {
+        let mut world = World::new(3, 1, SettingsBuilder::zero());
+        world.set_entity(0, 0, Entity::Wall, None, None);
+        world.set_corpse(1, 0, 5);
+
+        assert_eq!(1, world.spawn_random(10));
+        assert_eq!(1, world.population());
+        match world.get_entity(2, 0) {
+            Entity::Cell(_) => {},
+            _ => panic!("Immigrant should land on the only empty square")
+        }
+    }
+
+    #[test]
+    fn test_immigration_and_reseed() {
+        let settings = SettingsBuilder::prod().with_immigration_rate(2).build();
+        let mut world = World::new(5, 5, settings);
+        world.immigrate();
+        world.immigrate();
+        assert_eq!(4, world.population());
+
+        // Reseed tops population up to the floor, immigrants are part of it
+        world.update_settings(SettingsBuilder::prod().with_immigration_rate(2).with_reseed_floor(10).build());
+        world.immigrate();
+        assert_eq!(10, world.population());
+        world.immigrate();
+        assert_eq!(12, world.population());
+    }
+}
@@ -5,9 +5,26 @@ pub mod genome;
 pub mod settings;
 
 mod action;
+mod aging;
+mod brain;
+mod archive;
 mod genome_storage;
 mod genome_desc;
+mod immigration;
+mod light;
+mod lineage;
+mod phylogeny;
+mod species;
 mod cell_state_storage;
 mod cell_state;
 mod direction;
+mod events;
+mod gene_registry;
+mod map;
+mod neural;
+mod nutrients;
+mod observers;
+mod regions;
+mod schedule;
+mod signals;
 
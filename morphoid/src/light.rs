@@ -0,0 +1,110 @@
+use std::f64::consts::PI;
+
+use crate::types::*;
+
+impl World {
+    /// Share of the photosynthesis a cell gets at the place at the current tick
+    pub fn light(&self, x: Coords, y: Coords) -> f64 {
+        let settings = self.settings_at(x, y);
+        let tick = self.tick_no;
+
+        let depth = if self.height > 1 {
+            let level = if self.light_flipped { self.height - 1 - y } else { y };
+            level as f64 / (self.height - 1) as f64
+        } else {
+            0.0
+        };
+        let mut light = 1.0 - settings.light_depth_falloff() * depth;
+
+        if settings.sunspot_size() > 0 {
+            let patch_x = x.div_euclid(settings.sunspot_size());
+            let patch_y = y.div_euclid(settings.sunspot_size());
+            light *= 1.0 - settings.sunspot_contrast() * World::noise(patch_x, patch_y);
+        }
+
+        let day_length = settings.day_length();
+        if day_length > 0 && tick % day_length >= day_length / 2 {
+            light *= settings.night_light();
+        }
+
+        let season_length = settings.season_length();
+        if season_length > 0 {
+            let phase = (tick % season_length) as f64 / season_length as f64;
+            light *= 1.0 + settings.season_amplitude() * (2.0 * PI * phase).sin();
+        }
+
+        light.max(0.0)
+    }
+
+    // Stable pseudo random value in 0..1 for the patch
+    fn noise(x: Coords, y: Coords) -> f64 {
+        let mut value = (x as u32).wrapping_mul(374_761_393) ^ (y as u32).wrapping_mul(668_265_263);
+        value = (value ^ (value >> 13)).wrapping_mul(1_274_126_177);
+        value ^= value >> 16;
+        (value % 1000) as f64 / 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_light() {
+        let world = World::prod(3, 3);
+        assert_eq!(1.0, world.light(0, 0));
+        assert_eq!(1.0, world.light(2, 2));
+    }
+
+    #[test]
+    fn test_depth_and_night() {
+        let settings = SettingsBuilder::prod()
+            .with_light_depth_falloff(0.5)
+            .with_day_length(4)
+            .with_night_light(0.5)
+            .build();
+        let mut world = World::new(1, 3, settings);
+
+        assert_eq!(1.0, world.light(0, 0));
+        assert_eq!(0.75, world.light(0, 1));
+        assert_eq!(0.5, world.light(0, 2));
+
+        world.tick_no = 2;
+        assert_eq!(0.5, world.light(0, 0));
+        assert_eq!(0.25, world.light(0, 2));
+
+        world.tick_no = 4;
+        assert_eq!(1.0, world.light(0, 0));
+    }
+
+    #[test]
+    fn test_sunspots_are_patchy() {
+        let settings = SettingsBuilder::prod()
+            .with_sunspot_size(2)
+            .with_sunspot_contrast(1.0)
+            .build();
+        let world = World::new(20, 20, settings);
+
+        assert_eq!(world.light(0, 0), world.light(1, 1));
+        let lights: Vec<f64> = (0..10).map(|patch| world.light(patch * 2, 0)).collect();
+        assert!(lights.iter().any(|light| *light != lights[0]));
+        assert!(lights.iter().all(|light| *light >= 0.0 && *light <= 1.0));
+    }
+
+    #[test]
+    fn test_photosynthesis_depends_on_light() {
+        let settings = SettingsBuilder::prod()
+            .with_photosynthesis_adds(10)
+            .with_light_depth_falloff(1.0)
+            .build();
+
+        let mut world = World::new(1, 2, settings);
+        world.set_cell(0, 0, Genome::new_plant());
+        world.set_cell(0, 1, Genome::new_plant());
+        world.tick(&mut Processor::new());
+
+        let initial = world.get_settings().initial_cell_health();
+        assert_eq!(initial + 10, world.get_state_by_pos(0, 0).unwrap().health);
+        assert_eq!(initial, world.get_state_by_pos(0, 1).unwrap().health);
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::types::*;
+
+impl LineageStorage {
+    pub fn new() -> LineageStorage {
+        LineageStorage { records: HashMap::new() }
+    }
+
+    pub fn born(&mut self, genome: &Genome) {
+        self.born_as(genome, GenomeDesc::build_from(genome).icon());
+    }
+
+    pub fn born_as(&mut self, genome: &Genome, category: char) {
+        let id = genome.id();
+
+        if let Some(parent_id) = genome.parent_id() {
+            if let Some(parent) = self.records.get_mut(&parent_id) {
+                parent.children.push(id);
+            }
+        }
+
+        self.records.insert(id, LineageRecord {
+            parent_id: genome.parent_id(),
+            birth_tick: genome.birth_tick(),
+            death_tick: None,
+            mutations: genome.mutations.clone(),
+            children: Vec::new(),
+            category,
+        });
+    }
+
+    pub fn died(&mut self, id: GenomeId, tick: Tick) {
+        if let Some(record) = self.records.get_mut(&id) {
+            record.death_tick = Some(tick);
+        }
+        self.prune(id);
+    }
+
+    pub fn get(&self, id: GenomeId) -> Option<&LineageRecord> {
+        self.records.get(&id)
+    }
+
+    pub fn is_alive(&self, id: GenomeId) -> bool {
+        self.records.get(&id).map_or(false, |record| record.death_tick.is_none())
+    }
+
+    /// Parent first, root of the lineage last
+    pub fn ancestors(&self, id: GenomeId) -> Vec<GenomeId> {
+        let mut result = Vec::new();
+        let mut current = self.records.get(&id).and_then(|record| record.parent_id);
+
+        while let Some(parent_id) = current {
+            match self.records.get(&parent_id) {
+                Some(record) => {
+                    result.push(parent_id);
+                    current = record.parent_id;
+                },
+                None => break
+            }
+        }
+        result
+    }
+
+    /// All known descendants, closest generations first
+    pub fn descendants(&self, id: GenomeId) -> Vec<GenomeId> {
+        let mut result = Vec::new();
+        let mut queue: VecDeque<GenomeId> = VecDeque::new();
+        queue.push_back(id);
+
+        while let Some(current) = queue.pop_front() {
+            if let Some(record) = self.records.get(&current) {
+                for child in record.children.iter() {
+                    result.push(*child);
+                    queue.push_back(*child);
+                }
+            }
+        }
+        result
+    }
+
+    /// Most recent common ancestor, one of the genomes could be an ancestor of the other
+    pub fn common_ancestor(&self, first: GenomeId, second: GenomeId) -> Option<GenomeId> {
+        if !self.records.contains_key(&first) || !self.records.contains_key(&second) {
+            return None;
+        }
+
+        let mut first_line: HashSet<GenomeId> = self.ancestors(first).into_iter().collect();
+        first_line.insert(first);
+
+        if first_line.contains(&second) {
+            return Some(second);
+        }
+
+        self.ancestors(second)
+            .into_iter()
+            .find(|id| first_line.contains(id))
+    }
+
+    // Removes dead records without children, going up the lineage
+    fn prune(&mut self, id: GenomeId) {
+        let mut current = Some(id);
+
+        while let Some(id) = current {
+            let removable = match self.records.get(&id) {
+                Some(record) => record.death_tick.is_some() && record.children.is_empty(),
+                None => false
+            };
+            if !removable {
+                break;
+            }
+
+            let record = self.records.remove(&id).unwrap();
+            current = record.parent_id;
+
+            if let Some(parent_id) = record.parent_id {
+                if let Some(parent) = self.records.get_mut(&parent_id) {
+                    parent.children.retain(|child| *child != id);
+                }
+            }
+        }
+    }
+}
+
+impl Default for LineageStorage {
+    fn default() -> LineageStorage {
+        LineageStorage::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // root -> child1 -> grandchild
+    //      -> child2
+    fn build_family(storage: &mut LineageStorage) -> (GenomeId, GenomeId, GenomeId, GenomeId) {
+        let root = Genome::new_plant();
+        let child1 = root.new_child();
+        let child2 = root.new_child();
+        let grandchild = child1.new_child();
+
+        for genome in [&root, &child1, &child2, &grandchild].iter() {
+            storage.born(genome);
+        }
+
+        (root.id(), child1.id(), child2.id(), grandchild.id())
+    }
+
+    #[test]
+    fn test_ancestors_and_descendants() {
+        let mut storage = LineageStorage::new();
+        let (root, child1, child2, grandchild) = build_family(&mut storage);
+
+        assert_eq!(vec![child1, root], storage.ancestors(grandchild));
+        assert!(storage.ancestors(root).is_empty());
+
+        assert_eq!(vec![child1, child2, grandchild], storage.descendants(root));
+        assert_eq!(vec![grandchild], storage.descendants(child1));
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let mut storage = LineageStorage::new();
+        let (root, child1, child2, grandchild) = build_family(&mut storage);
+
+        assert_eq!(Some(root), storage.common_ancestor(grandchild, child2));
+        assert_eq!(Some(child1), storage.common_ancestor(grandchild, child1));
+        assert_eq!(Some(child1), storage.common_ancestor(child1, grandchild));
+        assert_eq!(None, storage.common_ancestor(root, 1_000_000));
+
+        let stranger = Genome::new_plant();
+        storage.born(&stranger);
+        assert_eq!(None, storage.common_ancestor(stranger.id(), child2));
+    }
+
+    #[test]
+    fn test_pruning() {
+        let mut storage = LineageStorage::new();
+        let (root, child1, child2, grandchild) = build_family(&mut storage);
+
+        // Has living descendants, so it stays
+        storage.died(root, 5);
+        storage.died(child1, 6);
+        assert!(storage.get(root).is_some());
+        assert_eq!(Some(6), storage.get(child1).unwrap().death_tick);
+        assert!(!storage.is_alive(child1));
+
+        // The whole branch goes away
+        storage.died(grandchild, 7);
+        assert!(storage.get(grandchild).is_none());
+        assert!(storage.get(child1).is_none());
+        assert_eq!(vec![child2], storage.get(root).unwrap().children);
+
+        storage.died(child2, 8);
+        assert!(storage.records.is_empty());
+    }
+}
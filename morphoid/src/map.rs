@@ -0,0 +1,79 @@
+use std::fs;
+use std::io;
+
+use crate::types::*;
+
+impl World {
+    /// Map is a text, one line per row: `#` is a wall, `.` is an empty square,
+    /// anything else leaves the square as it is. Lines and rows outside of the world are ignored.
+    pub fn apply_map(&mut self, map: &str) {
+        for (y, line) in map.lines().enumerate() {
+            for (x, symbol) in line.chars().enumerate() {
+                let (x, y) = (x as Coords, y as Coords);
+                if x >= self.width || y >= self.height {
+                    continue;
+                }
+                match symbol {
+                    '#' => self.set_entity(x, y, Entity::Wall, None, None),
+                    '.' => self.set_nothing(x, y),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Puts or removes a wall, squares with cells and corpses are left as they are.
+    /// Returns true if the square has changed
+    pub fn paint_wall(&mut self, x: Coords, y: Coords, wall: bool) -> bool {
+        match (*self.get_entity(x, y), wall) {
+            (Entity::Nothing, true) => self.set_entity(x, y, Entity::Wall, None, None),
+            (Entity::Wall, false) => self.set_nothing(x, y),
+            _ => return false
+        }
+        true
+    }
+
+    pub fn load_map(&mut self, path: &str) -> io::Result<()> {
+        let map = fs::read_to_string(path)?;
+        self.apply_map(&map);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_map() {
+        let mut world = World::prod(3, 2);
+        world.set_cell(1, 1, Genome::new_plant());
+        world.set_cell(2, 1, Genome::new_plant());
+
+        world.apply_map("#.#\n #.#\n###");
+
+        assert_eq!(Entity::Wall, *world.get_entity(0, 0));
+        assert_eq!(Entity::Nothing, *world.get_entity(1, 0));
+        assert_eq!(Entity::Wall, *world.get_entity(2, 0));
+        assert_eq!(Entity::Nothing, *world.get_entity(0, 1));
+        assert_eq!(Entity::Wall, *world.get_entity(1, 1));
+        assert_eq!(Entity::Nothing, *world.get_entity(2, 1));
+    }
+
+    #[test]
+    fn test_paint_wall() {
+        let mut world = World::prod(4, 1);
+        world.set_cell(1, 0, Genome::new_plant());
+        world.set_corpse(2, 0, 5);
+
+        assert!(world.paint_wall(0, 0, true));
+        assert!(!world.paint_wall(1, 0, true));
+        assert!(!world.paint_wall(1, 0, false));
+        assert!(!world.paint_wall(2, 0, false));
+        assert!(!world.paint_wall(3, 0, false));
+        assert_eq!("#o+ \n", format!("{}", world));
+
+        assert!(world.paint_wall(0, 0, false));
+        assert_eq!(Entity::Nothing, *world.get_entity(0, 0));
+    }
+}
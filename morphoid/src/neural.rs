@@ -0,0 +1,150 @@
+use rand::RngCore;
+
+use crate::types::*;
+
+impl NeuralBrain {
+    // Bias, health, light and one input per thing the cell could see in front of it
+    const INPUTS: usize = 6;
+    const HIDDEN: usize = 4;
+    // Photosynthesis, absorb, move, turn, attack, reproduce
+    const OUTPUTS: usize = 6;
+
+    pub fn new() -> NeuralBrain {
+        NeuralBrain
+    }
+
+    /// Genes in the middle of the range are zero weights
+    pub fn weight(gene: Gene) -> f64 {
+        (gene as f64 - (GENE_COUNT / 2) as f64) / (GENE_COUNT / 4) as f64
+    }
+
+    fn inputs(&self, x: Coords, y: Coords, genome_id: GenomeId, perceptor: &dyn Perceptor, settings: &Settings) -> [f64; NeuralBrain::INPUTS] {
+        let health = perceptor.get_state(genome_id).health as f64 / settings.initial_cell_health().max(1) as f64;
+        let ahead = match perceptor.looking_at(x, y) {
+            Some((target_x, target_y)) => *perceptor.get_entity(target_x, target_y),
+            // Edge of the world is a wall too
+            None => Entity::Wall
+        };
+
+        let mut inputs = [1.0, health, perceptor.light_at(x, y), 0.0, 0.0, 0.0];
+        match ahead {
+            Entity::Nothing => {},
+            Entity::Cell(_) => inputs[3] = 1.0,
+            Entity::Corpse(_) => inputs[4] = 1.0,
+            Entity::Wall => inputs[5] = 1.0,
+        }
+        inputs
+    }
+
+    // Index of the strongest output, the first one wins a tie
+    fn decide(&self, genes: &Genes, inputs: &[f64; NeuralBrain::INPUTS]) -> usize {
+        let mut hidden = [0.0; NeuralBrain::HIDDEN];
+        for (h, value) in hidden.iter_mut().enumerate() {
+            let sum: f64 = inputs.iter()
+                .enumerate()
+                .map(|(i, input)| input * NeuralBrain::weight(genes[h * NeuralBrain::INPUTS + i]))
+                .sum();
+            *value = sum.tanh();
+        }
+
+        let offset = NeuralBrain::INPUTS * NeuralBrain::HIDDEN;
+        let mut best = 0;
+        let mut best_output = f64::MIN;
+        for o in 0..NeuralBrain::OUTPUTS {
+            let output: f64 = hidden.iter()
+                .enumerate()
+                .map(|(h, value)| value * NeuralBrain::weight(genes[offset + o * NeuralBrain::HIDDEN + h]))
+                .sum();
+            if output > best_output {
+                best_output = output;
+                best = o;
+            }
+        }
+        best
+    }
+}
+
+/// Small network with fixed topology, first genes of the genome are its weights.
+/// Does one thing per turn, whatever the number of steps is
+impl Brain for NeuralBrain {
+    fn think(&mut self, x: Coords, y: Coords, genome_id: GenomeId, perceptor: &dyn Perceptor, settings: &Settings) -> Vec<Box<dyn Action>> {
+        let genome = perceptor.get_genome(genome_id).unwrap(); // should never happen
+        let inputs = self.inputs(x, y, genome_id, perceptor, settings);
+
+        let action: Box<dyn Action> = match self.decide(&genome.genes, &inputs) {
+            0 => {
+                let adds = (settings.photosynthesis_adds() as f64 * inputs[2]).round() as HealthType;
+                Box::new(UpdateHealthAction::new(x, y, adds))
+            },
+            1 => Box::new(AbsorbAction::new(x, y, settings.absorb_adds())),
+            2 => Box::new(MoveAction::new(x, y)),
+            3 => Box::new(RotateAction::new(x, y, 1)),
+            4 => Box::new(AttackAction::new(x, y, settings.attack_damage())),
+            _ => Box::new(ReproduceAction::new(x, y)),
+        };
+        vec![action]
+    }
+
+    fn mutate_gene(&self, gene: Gene, rng: &mut dyn RngCore) -> Gene {
+        BrainKind::Neural.mutate_gene(gene, rng)
+    }
+}
+
+impl Default for NeuralBrain {
+    fn default() -> NeuralBrain {
+        NeuralBrain::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neural_world(width: Coords) -> World {
+        let settings = SettingsBuilder::prod()
+            .with_brain(BrainKind::Neural)
+            .build();
+        World::new(width, 1, settings)
+    }
+
+    #[test]
+    fn test_weight() {
+        assert_eq!(0.0, NeuralBrain::weight(GENE_COUNT / 2));
+        assert_eq!(-2.0, NeuralBrain::weight(0));
+        assert!(NeuralBrain::weight(GENE_COUNT - 1) < 2.0);
+    }
+
+    #[test]
+    fn integration_plant_stays_in_place() {
+        let mut processor = Processor::new();
+        let mut world = neural_world(3);
+
+        let plant = Genome::new_plant();
+        let hash = plant.id();
+        world.set_cell_ext(1, 0, plant, Direction::East);
+
+        world.tick(&mut processor);
+        assert_eq!(Entity::Cell(hash), *world.get_entity(1, 0));
+        // Gene machine has not run
+        assert!(processor.genome_states.is_empty());
+    }
+
+    #[test]
+    fn integration_attack_weights() {
+        let mut processor = Processor::new();
+        let mut world = neural_world(2);
+
+        // Bias drives the first hidden neuron, which drives the attack output
+        let mut predator = Genome::new_plant();
+        predator.mutate(0, GENE_COUNT - 1);
+        predator.mutate(NeuralBrain::INPUTS * NeuralBrain::HIDDEN + 4 * NeuralBrain::HIDDEN, GENE_COUNT - 1);
+        world.set_cell_ext(0, 0, predator, Direction::East);
+        world.set_cell(1, 0, Genome::new_plant());
+
+        world.tick(&mut processor);
+        match world.get_entity(1, 0) {
+            Entity::Corpse(_) => {},
+            other => panic!("plant is not killed: {:?}", other)
+        }
+    }
+}
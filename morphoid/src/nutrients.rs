@@ -0,0 +1,118 @@
+use crate::types::*;
+
+impl World {
+    pub fn nutrients_at(&self, x: Coords, y: Coords) -> f64 {
+        self.nutrients[self.get_index(x, y)]
+    }
+
+    pub fn add_nutrients(&mut self, x: Coords, y: Coords, amount: f64) {
+        let index = self.get_index(x, y);
+        self.nutrients[index] += amount;
+    }
+
+    /// Takes whole units only, but not more than there is
+    pub fn take_nutrients(&mut self, x: Coords, y: Coords, max_amount: HealthType) -> HealthType {
+        let index = self.get_index(x, y);
+        let taken = (self.nutrients[index].floor() as HealthType).min(max_amount).max(0);
+        self.nutrients[index] -= taken as f64;
+        taken
+    }
+
+    pub fn diffuse_nutrients(&mut self) {
+        let diffusion = self.settings.nutrient_diffusion();
+        if diffusion > 0.0 {
+            self.nutrients = self.diffuse(&self.nutrients, diffusion);
+        }
+    }
+
+    /// Every square gives a share of its value equally to four neighbours,
+    /// nothing is given over the edge of the world
+    pub(crate) fn diffuse(&self, field: &[f64], diffusion: f64) -> Vec<f64> {
+        let mut result = field.to_vec();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = self.get_index(x, y);
+                let amount = field[index];
+                if amount <= 0.0 {
+                    continue;
+                }
+
+                let neighbours: Vec<usize> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                    .iter()
+                    .filter_map(|(dx, dy)| self.resolve(x + dx, y + dy))
+                    .map(|(nx, ny)| self.get_index(nx, ny))
+                    .collect();
+                if neighbours.is_empty() {
+                    continue;
+                }
+
+                let share = amount * diffusion / neighbours.len() as f64;
+                result[index] -= amount * diffusion;
+                for neighbour in neighbours {
+                    result[neighbour] += share;
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diffusion() {
+        let settings = SettingsBuilder::prod().with_nutrient_diffusion(0.4).build();
+        let mut world = World::new(3, 3, settings);
+        world.add_nutrients(1, 1, 10.0);
+
+        world.diffuse_nutrients();
+
+        assert_eq!(6.0, world.nutrients_at(1, 1));
+        assert_eq!(1.0, world.nutrients_at(1, 0));
+        assert_eq!(1.0, world.nutrients_at(2, 1));
+        assert_eq!(0.0, world.nutrients_at(0, 0));
+        assert_eq!(10.0, world.nutrients.iter().sum::<f64>());
+    }
+
+    #[test]
+    fn test_diffusion_keeps_at_walls() {
+        let settings = SettingsBuilder::prod()
+            .with_nutrient_diffusion(0.5)
+            .with_topology(Topology::Box)
+            .build();
+        let mut world = World::new(2, 1, settings);
+        world.add_nutrients(0, 0, 4.0);
+
+        world.diffuse_nutrients();
+
+        assert_eq!(2.0, world.nutrients_at(0, 0));
+        assert_eq!(2.0, world.nutrients_at(1, 0));
+    }
+
+    #[test]
+    fn integration_test_corpses_feed_absorbers() {
+        let settings = SettingsBuilder::prod()
+            .with_corpse_decay(-4)
+            .with_nutrient_diffusion(0.0)
+            .with_absorb_adds(3)
+            .with_absorb_cost(0)
+            .build();
+        let mut world = World::new(2, 1, settings);
+        let initial_health = world.get_settings().initial_cell_health();
+        world.set_corpse(0, 0, 6);
+        world.set_cell(1, 0, Genome::from_genes([ABSORB; GENOME_LENGTH]));
+
+        // Corpse releases 4, then 2 and disappears
+        world.tick(&mut Processor::new());
+        world.tick(&mut Processor::new());
+        assert_eq!(Entity::Nothing, *world.get_entity(0, 0));
+        assert_eq!(6.0, world.nutrients_at(0, 0));
+
+        world.nutrients[1] = 5.0;
+        world.tick(&mut Processor::new());
+        assert_eq!(initial_health + 3, world.get_state_by_pos(1, 0).unwrap().health);
+        assert_eq!(2.0, world.nutrients_at(1, 0));
+    }
+}
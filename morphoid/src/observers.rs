@@ -0,0 +1,157 @@
+use crate::types::*;
+
+impl World {
+    pub fn add_observer(&mut self, observer: Box<dyn WorldObserver>) {
+        self.observers.push(observer);
+    }
+
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    // Should be called every time global settings or regions change
+    pub(crate) fn settings_changed(&mut self) {
+        self.rebuild_regions();
+        for observer in self.observers.iter_mut() {
+            observer.settings_changed(&self.settings);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    // Keeps what has happened as text, shared with the test
+    struct Recorder {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&mut self, line: String) {
+            self.log.lock().unwrap().push(line);
+        }
+    }
+
+    impl WorldObserver for Recorder {
+        fn tick_started(&mut self, tick: Tick) {
+            self.record(format!("start {}", tick));
+        }
+
+        fn tick_finished(&mut self, tick: Tick) {
+            self.record(format!("finish {}", tick));
+        }
+
+        fn cell_born(&mut self, x: Coords, y: Coords, _genome: &Genome) {
+            self.record(format!("born {} {}", x, y));
+        }
+
+        fn cell_died(&mut self, x: Coords, y: Coords, _genome_id: GenomeId, cause: DeathCause) {
+            self.record(format!("died {} {} {:?}", x, y, cause));
+        }
+
+        fn cell_moved(&mut self, _genome_id: GenomeId, from: (Coords, Coords), to: (Coords, Coords)) {
+            self.record(format!("moved {:?} {:?}", from, to));
+        }
+
+        fn attack_landed(&mut self, attacker: (Coords, Coords), target: (Coords, Coords), damage: HealthType) {
+            self.record(format!("attack {:?} {:?} {}", attacker, target, damage));
+        }
+
+        fn corpse_decayed(&mut self, x: Coords, y: Coords, remains: HealthType) {
+            self.record(format!("decayed {} {} {}", x, y, remains));
+        }
+
+        fn settings_changed(&mut self, settings: &Settings) {
+            self.record(format!("settings {}", settings.move_cost()));
+        }
+    }
+
+    fn observed(world: &mut World) -> Arc<Mutex<Vec<String>>> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        world.add_observer(Box::new(Recorder { log: Arc::clone(&log) }));
+        log
+    }
+
+    #[test]
+    fn integration_attack_and_decay() {
+        let settings = SettingsBuilder::prod()
+            .with_topology(Topology::Box)
+            .with_attack_damage(100)
+            .with_corpse_initial(20)
+            .with_corpse_decay(-2)
+            .build();
+        let mut processor = Processor::new();
+        let mut world = World::new(2, 1, settings);
+
+        world.set_cell_ext(0, 0, Genome::new_predator(), Direction::East);
+        world.set_cell(1, 0, Genome::new_plant());
+        let log = observed(&mut world);
+
+        world.tick(&mut processor);
+        world.tick(&mut processor);
+        assert_eq!(
+            vec![
+                "start 0", "attack (0, 0) (1, 0) 100", "died 1 0 Attack", "finish 0",
+                "start 1", "decayed 1 0 18", "finish 1",
+            ],
+            *log.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn integration_born_and_moved() {
+        let mut world = World::new(3, 1, SettingsBuilder::zero());
+        let log = observed(&mut world);
+
+        world.set_cell_ext(0, 0, Genome::new_plant(), Direction::East);
+        world.move_cell(0, 0);
+        world.set_nothing(1, 0);
+        assert_eq!(
+            vec!["born 0 0", "moved (0, 0) (1, 0)", "died 1 0 Killed"],
+            *log.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn integration_wrapped_coordinates() {
+        let settings = SettingsBuilder::prod()
+            .with_topology(Topology::Cylinder)
+            .with_attack_damage(1)
+            .build();
+        let mut world = World::new(3, 1, settings);
+        world.set_cell_ext(0, 0, Genome::new_plant(), Direction::West);
+        world.set_cell_ext(1, 0, Genome::new_plant(), Direction::East);
+        let log = observed(&mut world);
+
+        world.move_cell(0, 0);
+        world.attack(1, 0, 1);
+        assert_eq!(
+            vec!["moved (0, 0) (2, 0)", "attack (1, 0) (2, 0) 1"],
+            *log.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_regions_change_settings() {
+        let mut world = World::prod(2, 2);
+        let log = observed(&mut world);
+
+        let area = RegionArea::Rect { x: 0, y: 0, width: 1, height: 1 };
+        world.add_region(Region::new("swamp", area, vec![("move_cost".to_string(), -1.0)])).unwrap();
+        world.clear_regions();
+        assert_eq!(vec!["settings -5", "settings -5"], *log.lock().unwrap());
+    }
+
+    #[test]
+    fn test_settings_changed() {
+        let mut world = World::prod(1, 1);
+        let log = observed(&mut world);
+
+        world.update_settings(SettingsBuilder::prod().with_move_cost(-7).build());
+        world.clear_observers();
+        world.update_settings(Settings::prod());
+        assert_eq!(vec!["settings -7"], *log.lock().unwrap());
+    }
+}
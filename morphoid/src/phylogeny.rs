@@ -0,0 +1,251 @@
+use std::fmt::Write;
+
+use crate::types::*;
+
+impl LineageStorage {
+    /// Phylogeny of the current population, one tree per original ancestor
+    pub fn phylogeny(&self) -> Phylogeny {
+        let mut root_ids: Vec<GenomeId> = self.records
+            .iter()
+            .filter(|(_, record)| match record.parent_id {
+                Some(parent_id) => !self.records.contains_key(&parent_id),
+                None => true
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        root_ids.sort();
+
+        let roots = root_ids
+            .into_iter()
+            .map(|id| self.build_tree(id))
+            .collect();
+
+        Phylogeny { roots }
+    }
+
+    // Lineages could be very deep, so the tree is built with explicit stack, children first
+    fn build_tree(&self, root_id: GenomeId) -> PhyloNode {
+        // Genome, birth tick of its parent, and whether its children are built already
+        let mut stack: Vec<(GenomeId, Option<Tick>, bool)> = vec![(root_id, None, false)];
+        let mut built: Vec<PhyloNode> = Vec::new();
+
+        while let Some((id, parent_birth_tick, expanded)) = stack.pop() {
+            let record = self.records.get(&id).unwrap();
+            if expanded {
+                let children = built.split_off(built.len() - record.children.len());
+                built.push(self.build_node(id, parent_birth_tick, children));
+            } else {
+                stack.push((id, parent_birth_tick, true));
+                // Reversed, so children are built in their order
+                for child_id in record.children.iter().rev() {
+                    stack.push((*child_id, Some(record.birth_tick), false));
+                }
+            }
+        }
+        built.pop().unwrap()
+    }
+
+    fn build_node(&self, id: GenomeId, parent_birth_tick: Option<Tick>, children: Vec<PhyloNode>) -> PhyloNode {
+        let record = self.records.get(&id).unwrap();
+
+        let alive = record.death_tick.is_none();
+        let population = children.iter().map(|child| child.population).sum::<usize>()
+            + if alive { 1 } else { 0 };
+        let branch_length = parent_birth_tick
+            .map_or(0, |parent_birth_tick| record.birth_tick - parent_birth_tick);
+
+        // Dead ancestor with a single line of descent adds nothing but a branch
+        if !alive && children.len() == 1 {
+            let mut child = children.into_iter().next().unwrap();
+            let mut mutations = record.mutations.clone();
+            mutations.append(&mut child.mutations);
+
+            child.branch_length += branch_length;
+            child.mutations = mutations;
+            return child;
+        }
+
+        PhyloNode {
+            genome_id: id,
+            alive,
+            branch_length,
+            mutations: record.mutations.clone(),
+            population,
+            category: record.category,
+            children,
+        }
+    }
+}
+
+impl World {
+    pub fn phylogeny(&self) -> Phylogeny {
+        self.lineage.phylogeny()
+    }
+}
+
+impl Phylogeny {
+    /// Several trees are joined under unnamed root, so the result is always one tree
+    pub fn to_newick(&self) -> String {
+        let mut result = String::new();
+
+        match self.roots.len() {
+            0 => {},
+            1 => Phylogeny::write_newick(&self.roots[0], &mut result),
+            _ => {
+                result.push('(');
+                for (i, root) in self.roots.iter().enumerate() {
+                    if i > 0 { result.push(','); }
+                    Phylogeny::write_newick(root, &mut result);
+                }
+                result.push(')');
+            }
+        }
+
+        result.push(';');
+        result
+    }
+
+    // Explicit stack of what is left to write, deep trees would overflow the call stack
+    fn write_newick(root: &PhyloNode, result: &mut String) {
+        let mut stack: Vec<NewickStep> = vec![NewickStep::Node(root)];
+
+        while let Some(step) = stack.pop() {
+            match step {
+                NewickStep::Node(node) if node.children.is_empty() => Phylogeny::write_label(node, result),
+                NewickStep::Node(node) => {
+                    // Pushed backwards: ( child , child ) label
+                    stack.push(NewickStep::Label(node));
+                    stack.push(NewickStep::Text(')'));
+                    for (i, child) in node.children.iter().rev().enumerate() {
+                        if i > 0 { stack.push(NewickStep::Text(',')); }
+                        stack.push(NewickStep::Node(child));
+                    }
+                    stack.push(NewickStep::Text('('));
+                },
+                NewickStep::Label(node) => Phylogeny::write_label(node, result),
+                NewickStep::Text(text) => result.push(text),
+            }
+        }
+    }
+
+    // Annotations are in NHX format, mutations are index-old-new
+    fn write_label(node: &PhyloNode, result: &mut String) {
+        write!(result, "{}:{}[&&NHX:population={}:category={}",
+               node.genome_id, node.branch_length, node.population, node.category).unwrap();
+        if !node.mutations.is_empty() {
+            let mutations: Vec<String> = node.mutations
+                .iter()
+                .map(|mutation| format!("{}-{}-{}", mutation.index, mutation.old_gene, mutation.new_gene))
+                .collect();
+            write!(result, ":mutations={}", mutations.join("|")).unwrap();
+        }
+        result.push(']');
+    }
+}
+
+enum NewickStep<'a> {
+    Node(&'a PhyloNode),
+    Label(&'a PhyloNode),
+    Text(char),
+}
+
+// Deep trees are dropped without recursion too
+impl Drop for PhyloNode {
+    fn drop(&mut self) {
+        let mut stack: Vec<PhyloNode> = self.children.drain(..).collect();
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapses_dead_ancestors() {
+        let mut storage = LineageStorage::new();
+
+        // root -> middle -> (leaf1, leaf2), root and middle are dead
+        let root = Genome::new_plant();
+        let mut middle = root.new_child();
+        middle.birth_tick = 3;
+        middle.apply_mutation(0, ATTACK);
+        let mut leaf1 = middle.new_child();
+        leaf1.birth_tick = 5;
+        let mut leaf2 = middle.new_child();
+        leaf2.birth_tick = 10;
+        leaf2.apply_mutation(1, ATTACK);
+
+        for genome in [&root, &middle, &leaf1, &leaf2].iter() {
+            storage.born(genome);
+        }
+        storage.died(root.id(), 4);
+        storage.died(middle.id(), 11);
+
+        let phylogeny = storage.phylogeny();
+        assert_eq!(1, phylogeny.roots.len());
+
+        let node = &phylogeny.roots[0];
+        assert_eq!(middle.id(), node.genome_id);
+        assert_eq!(3, node.branch_length);
+        assert_eq!(2, node.population);
+        assert_eq!(1, node.mutations.len());
+        assert!(!node.alive);
+
+        assert_eq!(2, node.children[0].branch_length);
+        assert_eq!(7, node.children[1].branch_length);
+        assert_eq!(1, node.children[1].mutations.len());
+        assert_eq!('o', node.children[1].category);
+
+        assert_eq!(
+            format!(
+                "({}:2[&&NHX:population=1:category=o],{}:7[&&NHX:population=1:category=o:mutations=1-31-29])\
+                 {}:3[&&NHX:population=2:category=o:mutations=0-31-29];",
+                leaf1.id(), leaf2.id(), middle.id()
+            ),
+            phylogeny.to_newick()
+        );
+    }
+
+    #[test]
+    fn test_newick_forest() {
+        let mut storage = LineageStorage::new();
+        assert_eq!(";", storage.phylogeny().to_newick());
+
+        let first = Genome::new_plant();
+        let second = Genome::new_predator();
+        storage.born(&first);
+        storage.born(&second);
+
+        assert_eq!(
+            format!(
+                "({}:0[&&NHX:population=1:category=o],{}:0[&&NHX:population=1:category=x]);",
+                first.id(), second.id()
+            ),
+            storage.phylogeny().to_newick()
+        );
+    }
+
+    #[test]
+    fn test_deep_lineage() {
+        let mut storage = LineageStorage::new();
+        let mut genome = Genome::new_plant();
+        let root_id = genome.id();
+        storage.born(&genome);
+        for tick in 1..100_000 {
+            genome = genome.new_child();
+            genome.birth_tick = tick;
+            storage.born(&genome);
+        }
+
+        let phylogeny = storage.phylogeny();
+        assert_eq!(root_id, phylogeny.roots[0].genome_id);
+        assert_eq!(100_000, phylogeny.roots[0].population);
+
+        let newick = phylogeny.to_newick();
+        assert!(newick.starts_with(&"(".repeat(99_999)));
+        assert!(newick.ends_with(&format!("{}:0[&&NHX:population=100000:category=o];", root_id)));
+    }
+}
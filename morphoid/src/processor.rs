@@ -1,4 +1,5 @@
 use crate::types::*;
+use std::cmp;
 use std::collections::HashMap;
 
 impl Processor {
@@ -6,13 +7,23 @@ impl Processor {
         Processor { genome_states: HashMap::new() }
     }
 
-    pub fn process_entity(&mut self, x:Coords, y:Coords, entity: Entity, perceptor: &Perceptor, settings: &Settings) -> Vec<Box<dyn Action>> {
+    /// Cells are thought for by the brain if there is one, by the gene machine otherwise
+    pub fn process_entity(&mut self, x:Coords, y:Coords, entity: Entity, perceptor: &Perceptor, settings: &Settings,
+                          brain: &mut Option<Box<dyn Brain>>) -> Vec<Box<dyn Action>> {
         let mut all_actions:Vec<Box<dyn Action>> = Vec::new();
         match entity {
             Entity::Cell(genome_id) => {
                 //println!("DEBUG: Processor.process_entity [cell] ---- x: {:?} y:{:?}, genome: {:?}", x, y, genome_id);
-                let mut actions = self.execute(x, y, genome_id, perceptor, settings);
+                let mut actions = match brain {
+                    Some(brain) => brain.think(x, y, genome_id, perceptor, settings),
+                    None => self.think(x, y, genome_id, perceptor, settings),
+                };
                 all_actions.append(&mut actions);
+
+                let metabolism = self.metabolism(genome_id, perceptor, settings);
+                if metabolism != 0 {
+                    all_actions.push(Box::new(UpdateHealthAction::new(x, y, metabolism)));
+                }
             },
             Entity::Corpse(_) => {
                 all_actions.push(Box::new(DecayAction::new(x, y, settings.corpse_decay())));
@@ -37,6 +48,10 @@ impl Processor {
         let genome = perceptor.get_genome(genome_id).unwrap(); // should never happen
         let start_index = self.get_genome_index(genome_id);
 
+        // Local copy, so the register genes see each other's changes during the turn
+        let old_registers = perceptor.get_state(genome_id).registers;
+        let mut registers = old_registers;
+
         let mut index = start_index;
         for _ in 0..settings.steps_per_turn() {
             let gene = genome.genes[index];
@@ -57,7 +72,17 @@ impl Processor {
                     index += 1
                 },
                 PHOTOSYNTHESIS => {
-                    actions.push(Box::new(UpdateHealthAction::new(x, y, settings.photosynthesis_adds())));
+                    let light = perceptor.light_at(x, y);
+                    let adds = (settings.photosynthesis_adds() as f64 * light).round() as HealthType;
+                    actions.push(Box::new(UpdateHealthAction::new(x, y, adds)));
+                    index += 1
+                },
+                ABSORB => {
+                    actions.push(Box::new(AbsorbAction::new(x, y, settings.absorb_adds())));
+                    index += 1
+                },
+                DEFEND => {
+                    actions.push(Box::new(DefendAction::new(x, y, settings.defend_duration())));
                     index += 1
                 },
                 MOVE => {
@@ -65,24 +90,97 @@ impl Processor {
                     index += 1
                 },
                 TURN => {
-                    let new_direction = genome.genes[self.normalize_index(index + 1)] % Direction::SIZE;
+                    let new_direction = genome.genes[self.normalize_index(index + 1)] % settings.neighbourhood().size();
                     actions.push(Box::new(RotateAction::new(x, y, new_direction)));
                     index += 2
                 },
+                EMIT => {
+                    let channel = genome.genes[self.normalize_index(index + 1)] % SIGNAL_CHANNELS;
+                    actions.push(Box::new(EmitAction::new(x, y, channel, settings.emit_amount())));
+                    index += 2
+                },
+                SMELL => {
+                    let channel = genome.genes[self.normalize_index(index + 1)] % SIGNAL_CHANNELS;
+                    let direction = perceptor.get_state(genome_id).direction;
+                    let rotation = self.strongest_signal(x, y, channel, direction, settings.neighbourhood(), perceptor);
+                    actions.push(Box::new(SmellAction::new(x, y, rotation)));
+                    index += 2
+                },
                 SENSE => {
                     actions.push(Box::new(UpdateHealthAction::new(x, y, settings.sense_cost())));
-                    if let Some((target_x, target_y)) = perceptor.looking_at(x, y) {
-                        // This is just a conditional operator
-                        index += match perceptor.get_entity(target_x, target_y) {
-                            Entity::Nothing => 1,
-                            Entity::Cell(_) => 2,
-                            Entity::Corpse(_) => 3
-                        }
+                    // This is just a conditional operator
+                    index += self.sense_branch(perceptor.looking_at(x, y), perceptor);
+                },
+                LOOK => {
+                    let direction = perceptor.get_state(genome_id).direction;
+                    let range = settings.look_range();
+                    let (seen, distance) = self.look(x, y, direction, range, settings.neighbourhood(), perceptor);
+                    actions.push(Box::new(UpdateHealthAction::new(x, y, settings.look_cost() * distance)));
+
+                    // Same branches as SENSE, things in the far half of the range are 3 genes further
+                    index += match seen {
+                        Entity::Nothing => 1,
+                        _ if distance > cmp::max(range / 2, 1) => self.entity_branch(&seen) + 3,
+                        _ => self.entity_branch(&seen)
                     }
                 },
-                _ => {
-                    // Goto
-                    index = gene;
+                STORE_SENSE => {
+                    let register = genome.genes[self.normalize_index(index + 1)] % REGISTERS;
+                    actions.push(Box::new(UpdateHealthAction::new(x, y, settings.sense_cost())));
+                    registers[register] = self.sense_branch(perceptor.looking_at(x, y), perceptor) as i32;
+                    index += 2
+                },
+                INCREMENT => {
+                    let register = genome.genes[self.normalize_index(index + 1)] % REGISTERS;
+                    registers[register] = registers[register].saturating_add(1);
+                    index += 2
+                },
+                DECREMENT => {
+                    let register = genome.genes[self.normalize_index(index + 1)] % REGISTERS;
+                    registers[register] = registers[register].saturating_sub(1);
+                    index += 2
+                },
+                IF_ZERO => {
+                    let register = genome.genes[self.normalize_index(index + 1)] % REGISTERS;
+                    index += if registers[register] == 0 { 2 } else { 3 }
+                },
+                ATTACK_TOWARDS => {
+                    let rotation = genome.genes[self.normalize_index(index + 1)] % settings.neighbourhood().size();
+                    actions.push(Box::new(AttackAction::towards(x, y, rotation, settings.attack_damage())));
+                    index += 2
+                },
+                MOVE_TOWARDS => {
+                    let rotation = genome.genes[self.normalize_index(index + 1)] % settings.neighbourhood().size();
+                    actions.push(Box::new(MoveAction::towards(x, y, rotation)));
+                    index += 2
+                },
+                REPRODUCE_TOWARDS => {
+                    let rotation = genome.genes[self.normalize_index(index + 1)] % settings.neighbourhood().size();
+                    actions.push(Box::new(ReproduceAction::towards(x, y, rotation)));
+                    index += 2
+                },
+                SENSE_TOWARDS => {
+                    let rotation = genome.genes[self.normalize_index(index + 1)] % settings.neighbourhood().size();
+                    actions.push(Box::new(UpdateHealthAction::new(x, y, settings.sense_cost())));
+                    // Same branches as SENSE, counted after the operand
+                    index += 1 + self.sense_branch(perceptor.looking_towards(x, y, rotation), perceptor);
+                },
+                _ => match perceptor.gene_registry().get(gene) {
+                    Some(handler) => {
+                        let operands: Vec<Gene> = (1..=handler.operands())
+                            .map(|offset| genome.genes[(index + offset) % GENOME_LENGTH])
+                            .collect();
+                        let cost = handler.cost(settings);
+                        if cost != 0 {
+                            actions.push(Box::new(UpdateHealthAction::new(x, y, cost)));
+                        }
+                        actions.append(&mut handler.actions(x, y, genome_id, &operands, perceptor, settings));
+                        index += handler.branch(x, y, genome_id, &operands, perceptor, settings);
+                    },
+                    None => {
+                        // Goto
+                        index = gene;
+                    }
                 }
             }
 
@@ -95,9 +193,94 @@ impl Processor {
 //        println!("DEBUG: Processor.execute gene: {:?} start: {:?} steps: {:?} end: {:?}",
 //                 genome_id, start_index, settings.steps_per_turn(), index);
 
+        // Goes first, while the cell is still at its place
+        if registers != old_registers {
+            actions.insert(0, Box::new(SetRegistersAction::new(x, y, registers)));
+        }
         actions
     }
 
+    // Offset of the next gene depending on what is at the target square
+    pub(crate) fn sense_branch(&self, target: Option<(Coords, Coords)>, perceptor: &dyn Perceptor) -> GeneIndex {
+        match target {
+            Some((target_x, target_y)) => self.entity_branch(perceptor.get_entity(target_x, target_y)),
+            // Edge of the world is a wall too
+            None => 4
+        }
+    }
+
+    fn entity_branch(&self, entity: &Entity) -> GeneIndex {
+        match entity {
+            Entity::Nothing => 1,
+            Entity::Cell(_) => 2,
+            Entity::Corpse(_) => 3,
+            Entity::Wall => 4
+        }
+    }
+
+    // First thing along the direction and how far it is, the edge of the world is a wall
+    fn look(&self, x: Coords, y: Coords, direction: Direction, range: Coords,
+            neighbourhood: Neighbourhood, perceptor: &dyn Perceptor) -> (Entity, Coords) {
+        let (mut current_x, mut current_y) = (x, y);
+        for distance in 1..=range {
+            // Step by step, hex shift depends on the row
+            let (dx, dy) = neighbourhood.shift(direction, current_y);
+            current_x += dx;
+            current_y += dy;
+
+            match perceptor.resolve(current_x, current_y) {
+                Some((target_x, target_y)) => match perceptor.get_entity(target_x, target_y) {
+                    Entity::Nothing => {},
+                    entity => return (*entity, distance)
+                },
+                None => return (Entity::Wall, distance)
+            }
+        }
+        (Entity::Nothing, range)
+    }
+
+    // Basal cost of staying alive for one tick
+    fn metabolism(&self, genome_id: GenomeId, perceptor: &dyn Perceptor, settings: &Settings) -> HealthType {
+        let cost = settings.metabolism_cost();
+        if cost == 0 {
+            return 0;
+        }
+
+        let state = perceptor.get_state(genome_id);
+        let factor = match settings.metabolism_scaling() {
+            MetabolismScaling::Flat => 1.0,
+            MetabolismScaling::GenomeLength => {
+                let genome = perceptor.get_genome(genome_id).unwrap();
+                let registry = perceptor.gene_registry();
+                let coding = genome.genes.iter().filter(|gene| registry.is_known(**gene)).count();
+                coding as f64 / GENOME_LENGTH as f64
+            },
+            MetabolismScaling::Age => 1.0 + state.age as f64 / AGE_METABOLISM_PERIOD as f64,
+            MetabolismScaling::Health => {
+                state.health.max(0) as f64 / settings.initial_cell_health().max(1) as f64
+            }
+        };
+        (cost as f64 * factor).round() as HealthType
+    }
+
+    // Rotation toward the neighbour square with the strongest signal,
+    // cell keeps its direction if there is nothing stronger around
+    fn strongest_signal(&self, x: Coords, y: Coords, channel: usize, direction: Direction,
+                        neighbourhood: Neighbourhood, perceptor: &dyn Perceptor) -> Gene {
+        let mut best_rotation = 0;
+        let mut best_signal = perceptor.signal_at(x, y, channel);
+
+        for rotation in 0..neighbourhood.size() {
+            let (dx, dy) = neighbourhood.shift(neighbourhood.rotate(direction, rotation), y);
+            let signal = perceptor.signal_at(x + dx, y + dy, channel);
+            if signal > best_signal {
+                best_signal = signal;
+                best_rotation = rotation;
+            }
+        }
+        best_rotation
+    }
+
     fn normalize_index(&mut self, index: GeneIndex) -> GeneIndex {
         index % GENOME_LENGTH
     }
@@ -159,6 +342,140 @@ mod tests {
         }
     }
 
+    #[test]
+    fn integration_sense_sees_the_wall() {
+        let settings = SettingsBuilder::prod()
+            .with_topology(Topology::Box)
+            .with_sense_cost(0)
+            .build();
+
+        let mut processor = Processor::new();
+        let mut world = World::new(2, 1, settings);
+
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, SENSE);
+        let hash = genome.id();
+        world.set_cell_ext(0, 0, genome, Direction::West);
+
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 4);
+    }
+
+    #[test]
+    fn integration_sense_towards_skips_operand() {
+        let settings = SettingsBuilder::prod()
+            .with_topology(Topology::Box)
+            .with_sense_cost(0)
+            .build();
+
+        let mut processor = Processor::new();
+        let mut world = World::new(2, 1, settings);
+
+        // Looks to the right of North, there is a cell
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, SENSE_TOWARDS);
+        genome.mutate(1, 2);
+        let hash = genome.id();
+        world.set_cell_ext(0, 0, genome, Direction::North);
+        world.set_cell(1, 0, Genome::new_plant());
+
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 3);
+        assert_eq!(Direction::North, world.get_state(hash).direction);
+    }
+
+    #[test]
+    fn integration_move_towards_keeps_direction() {
+        let mut processor = Processor::new();
+        let mut world = World::new(3, 1, SettingsBuilder::zero());
+
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, MOVE_TOWARDS);
+        genome.mutate(1, 6);
+        let hash = genome.id();
+        world.set_cell_ext(1, 0, genome, Direction::North);
+
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 2);
+        assert_eq!(Some(&Direction::North), world.get_state_by_pos(0, 0).map(|state| &state.direction));
+    }
+
+    #[test]
+    fn integration_look_far_and_near() {
+        let initial_health = Settings::prod().initial_cell_health();
+        let build_world = |width| {
+            let settings = SettingsBuilder::prod()
+                .with_topology(Topology::Box)
+                .with_photosynthesis_adds(0)
+                .with_look_range(5)
+                .with_look_cost(-1)
+                .build();
+            World::new(width, 1, settings)
+        };
+
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, LOOK);
+
+        // Cell four squares away is in the far half of the range
+        let mut processor = Processor::new();
+        let mut world = build_world(6);
+        let looker = genome.new_child();
+        let hash = looker.id();
+        world.set_cell_ext(0, 0, looker, Direction::East);
+        world.set_cell(4, 0, Genome::new_plant());
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 5);
+        assert_eq!(initial_health - 4, world.get_state(hash).health);
+
+        // Edge right behind is a near wall
+        let mut processor = Processor::new();
+        let mut world = build_world(2);
+        let looker = genome.new_child();
+        let hash = looker.id();
+        world.set_cell_ext(1, 0, looker, Direction::East);
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 4);
+
+        // Nothing in range costs the whole range
+        let mut processor = Processor::new();
+        let mut world = build_world(10);
+        let looker = genome.new_child();
+        let hash = looker.id();
+        world.set_cell_ext(0, 0, looker, Direction::East);
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 1);
+        assert_eq!(initial_health - 5, world.get_state(hash).health);
+    }
+
+    #[test]
+    fn integration_registers() {
+        let settings = SettingsBuilder::prod()
+            .with_topology(Topology::Box)
+            .with_steps_per_turn(4)
+            .with_sense_cost(0)
+            .build();
+
+        let mut processor = Processor::new();
+        let mut world = World::new(1, 1, settings);
+
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, INCREMENT);
+        genome.mutate(1, 1);
+        genome.mutate(2, IF_ZERO);
+        genome.mutate(3, 1);
+        // Skipped, as register 1 is not zero
+        genome.mutate(4, DECREMENT);
+        genome.mutate(5, STORE_SENSE);
+        genome.mutate(6, 2 + REGISTERS);
+        let hash = genome.id();
+        world.set_cell_ext(0, 0, genome, Direction::North);
+
+        world.tick(&mut processor);
+        assert_eq!(processor.get_genome_index(hash), 8);
+        // The edge is behind the wall branch
+        assert_eq!([0, 1, 4, 0], world.get_state(hash).registers);
+    }
+
     #[test]
     fn integration_test_kill_action() {
         let mut world = World::prod(1, 1);
@@ -199,4 +516,41 @@ mod tests {
             _ => panic!("Cell should be dead here")
         }
     }
+
+    #[test]
+    fn integration_goto_loop_pays_metabolism() {
+        let settings = SettingsBuilder::prod().with_metabolism_cost(-2).build();
+        let mut world = World::new(1, 1, settings);
+        // Every gene jumps to the first one
+        world.set_cell(0, 0, Genome::from_genes([0; GENOME_LENGTH]));
+
+        for _ in 0..3 {
+            world.tick(&mut Processor::new());
+        }
+        assert_eq!(4, world.get_state_by_pos(0, 0).unwrap().health);
+    }
+
+    #[test]
+    fn test_metabolism_scaling() {
+        let mut world = World::prod(1, 1);
+        let mut genes = [0; GENOME_LENGTH];
+        for gene in genes.iter_mut().take(GENOME_LENGTH / 4) {
+            *gene = PHOTOSYNTHESIS;
+        }
+        let genome = Genome::from_genes(genes);
+        let genome_id = genome.id();
+        world.set_cell(0, 0, genome);
+        world.cell_states.get_mut(genome_id).age = 200;
+
+        let processor = Processor::new();
+        let metabolism = |scaling| {
+            let settings = SettingsBuilder::prod().with_metabolism_cost(-4).with_metabolism_scaling(scaling).build();
+            processor.metabolism(genome_id, &world, &settings)
+        };
+
+        assert_eq!(-4, metabolism(MetabolismScaling::Flat));
+        assert_eq!(-1, metabolism(MetabolismScaling::GenomeLength));
+        assert_eq!(-12, metabolism(MetabolismScaling::Age));
+        assert_eq!(-4, metabolism(MetabolismScaling::Health));
+    }
 }
@@ -0,0 +1,170 @@
+use crate::types::*;
+
+impl RegionArea {
+    /// Mask is a text like a map: `#` marks squares of the region
+    pub fn mask_from_text(text: &str) -> RegionArea {
+        RegionArea::Mask(
+            text.lines()
+                .map(|line| line.chars().map(|symbol| symbol == '#').collect())
+                .collect()
+        )
+    }
+
+    pub fn contains(&self, x: Coords, y: Coords) -> bool {
+        match self {
+            RegionArea::Rect { x: left, y: top, width, height } =>
+                x >= *left && x < left + width && y >= *top && y < top + height,
+            RegionArea::Mask(rows) => {
+                if x < 0 || y < 0 {
+                    return false;
+                }
+                rows.get(y as usize)
+                    .and_then(|row| row.get(x as usize))
+                    .cloned()
+                    .unwrap_or(false)
+            }
+        }
+    }
+}
+
+impl Region {
+    pub fn new(name: &str, area: RegionArea, overrides: Vec<(String, f64)>) -> Region {
+        Region { name: name.to_string(), area, overrides }
+    }
+
+    /// Fails on the first unknown setting or value out of its range
+    pub fn apply_to(&self, settings: &Settings) -> Result<Settings, String> {
+        let mut result = settings.clone();
+        for (name, value) in self.overrides.iter() {
+            result.set_by_name(name, *value)
+                .map_err(|error| format!("{} in region {:?}", error, self.name))?;
+        }
+        Ok(result)
+    }
+}
+
+impl World {
+    /// Settings for actions happening at the square
+    pub fn settings_at(&self, x: Coords, y: Coords) -> &Settings {
+        match self.region_map[self.get_index(x, y)] {
+            Some(region) => &self.region_settings[region],
+            None => &self.settings
+        }
+    }
+
+    pub fn add_region(&mut self, region: Region) -> Result<(), String> {
+        region.apply_to(&self.settings)?;
+        self.regions.push(region);
+        self.settings_changed();
+        Ok(())
+    }
+
+    pub fn clear_regions(&mut self) {
+        self.regions.clear();
+        self.settings_changed();
+    }
+
+    // Should be called every time regions or global settings change
+    pub(crate) fn rebuild_regions(&mut self) {
+        let settings = &self.settings;
+        self.region_settings = self.regions
+            .iter()
+            .map(|region| region.apply_to(settings).unwrap_or_else(|_| settings.clone()))
+            .collect();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = self.get_index(x, y);
+                self.region_map[index] = self.regions
+                    .iter()
+                    .rposition(|region| region.area.contains(x, y));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swamp() -> Region {
+        Region::new(
+            "swamp",
+            RegionArea::Rect { x: 1, y: 0, width: 2, height: 1 },
+            vec![("move_cost".to_string(), -20.0)]
+        )
+    }
+
+    #[test]
+    fn test_area_contains() {
+        let rect = RegionArea::Rect { x: 1, y: 1, width: 2, height: 1 };
+        assert!(rect.contains(1, 1));
+        assert!(rect.contains(2, 1));
+        assert!(!rect.contains(3, 1));
+        assert!(!rect.contains(1, 0));
+
+        let mask = RegionArea::mask_from_text(".#\n#");
+        assert!(mask.contains(1, 0));
+        assert!(mask.contains(0, 1));
+        assert!(!mask.contains(0, 0));
+        assert!(!mask.contains(1, 1));
+        assert!(!mask.contains(-1, 0));
+    }
+
+    #[test]
+    fn test_settings_at() {
+        let mut world = World::prod(3, 1);
+        world.add_region(swamp()).unwrap();
+
+        assert_eq!(-5, world.settings_at(0, 0).move_cost());
+        assert_eq!(-20, world.settings_at(1, 0).move_cost());
+        assert_eq!(-20, world.settings_at(2, 0).move_cost());
+        assert_eq!(-5, world.settings_at(2, 0).turn_cost());
+
+        // Global changes go through regions, overrides stay
+        world.update_settings(SettingsBuilder::prod().with_turn_cost(-7).with_move_cost(-1).build());
+        assert_eq!(-7, world.settings_at(2, 0).turn_cost());
+        assert_eq!(-20, world.settings_at(2, 0).move_cost());
+        assert_eq!(-1, world.settings_at(0, 0).move_cost());
+
+        world.clear_regions();
+        assert_eq!(-1, world.settings_at(2, 0).move_cost());
+    }
+
+    #[test]
+    fn test_unknown_override() {
+        let mut world = World::prod(3, 1);
+        let region = Region::new("moon", RegionArea::Rect { x: 0, y: 0, width: 1, height: 1 },
+                                 vec![("gravity".to_string(), 1.6)]);
+        assert!(world.add_region(region).is_err());
+        assert!(world.regions.is_empty());
+    }
+
+    #[test]
+    fn test_override_out_of_range() {
+        let mut world = World::prod(3, 1);
+        let region = Region::new("reactor", RegionArea::Rect { x: 0, y: 0, width: 1, height: 1 },
+                                 vec![("mutation_probability".to_string(), 1.5)]);
+        assert!(world.add_region(region).unwrap_err().contains("reactor"));
+        assert!(world.regions.is_empty());
+        assert_eq!(Settings::prod().mutation_probability(), world.settings_at(0, 0).mutation_probability());
+    }
+
+    #[test]
+    fn integration_test_moving_costs_more_in_swamp() {
+        let mut world = World::new(3, 1, SettingsBuilder::zero());
+        let mut swamp = swamp();
+        swamp.overrides.push(("initial_cell_health".to_string(), 100.0));
+        world.add_region(swamp).unwrap();
+
+        let mut mover = Genome::new_plant();
+        for i in 0..GENOME_LENGTH {
+            mover.mutate(i, MOVE);
+        }
+        world.set_cell_ext(1, 0, mover, Direction::East);
+
+        world.tick(&mut Processor::new());
+
+        assert_eq!(80, world.get_state_by_pos(2, 0).unwrap().health);
+    }
+}
@@ -0,0 +1,211 @@
+use std::f64::consts::PI;
+
+use crate::types::*;
+
+impl ScheduleShape {
+    /// Value at the number of ticks since start. Changes added after their start
+    /// are caught up with, so a late step or ramp still ends at its final value
+    pub fn value_at(&self, elapsed: Tick) -> f64 {
+        match self {
+            ScheduleShape::Step { value } => *value,
+            ScheduleShape::Ramp { from, to, duration } => {
+                if elapsed >= *duration {
+                    *to
+                } else {
+                    from + (to - from) * elapsed as f64 / *duration as f64
+                }
+            },
+            ScheduleShape::Periodic { base, amplitude, period } => {
+                if *period == 0 {
+                    return *base;
+                }
+                let phase = (elapsed % period) as f64 / *period as f64;
+                base + amplitude * (2.0 * PI * phase).sin()
+            }
+        }
+    }
+
+    /// Smallest and biggest values the shape ever takes
+    pub fn bounds(&self) -> (f64, f64) {
+        match self {
+            ScheduleShape::Step { value } => (*value, *value),
+            ScheduleShape::Ramp { from, to, .. } => (from.min(*to), from.max(*to)),
+            ScheduleShape::Periodic { base, amplitude, .. } => (base - amplitude.abs(), base + amplitude.abs())
+        }
+    }
+
+    fn is_finished(&self, elapsed: Tick) -> bool {
+        match self {
+            ScheduleShape::Step { .. } => elapsed >= 1,
+            ScheduleShape::Ramp { duration, .. } => elapsed > *duration,
+            ScheduleShape::Periodic { .. } => false
+        }
+    }
+}
+
+impl ScheduledChange {
+    pub fn new(setting: &str, start_tick: Tick, shape: ScheduleShape) -> ScheduledChange {
+        ScheduledChange { setting: setting.to_string(), start_tick, shape }
+    }
+
+    /// None before the start
+    pub fn value_at(&self, tick: Tick) -> Option<f64> {
+        if tick < self.start_tick {
+            return None;
+        }
+        Some(self.shape.value_at(tick - self.start_tick))
+    }
+}
+
+impl SettingsSchedule {
+    pub fn new() -> SettingsSchedule {
+        SettingsSchedule { changes: Vec::new() }
+    }
+
+    /// Changes added later win if they touch the same setting at the same tick.
+    /// Every value the change could take must be valid for the setting
+    pub fn add(&mut self, change: ScheduledChange) -> Result<(), String> {
+        let (min, max) = change.shape.bounds();
+        Settings::prod().set_by_name(&change.setting, min)?;
+        Settings::prod().set_by_name(&change.setting, max)?;
+        self.changes.push(change);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    /// Returns true if any setting has got a new value, finished changes are forgotten
+    pub fn apply(&mut self, settings: &mut Settings, tick: Tick) -> bool {
+        let before = settings.clone();
+        for change in self.changes.iter() {
+            if let Some(value) = change.value_at(tick) {
+                // Could not fail, values are checked when the change is added
+                let _ = settings.set_by_name(&change.setting, value);
+            }
+        }
+
+        self.changes.retain(|change| {
+            tick < change.start_tick || !change.shape.is_finished(tick - change.start_tick + 1)
+        });
+        *settings != before
+    }
+}
+
+impl World {
+    pub(crate) fn apply_schedule(&mut self) {
+        if self.schedule.apply(&mut self.settings, self.tick_no) {
+            self.settings_changed();
+        }
+    }
+}
+
+impl Default for SettingsSchedule {
+    fn default() -> SettingsSchedule {
+        SettingsSchedule::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shapes() {
+        let step = ScheduleShape::Step { value: 3.0 };
+        assert_eq!(3.0, step.value_at(0));
+        assert_eq!(3.0, step.value_at(1));
+
+        let ramp = ScheduleShape::Ramp { from: 10.0, to: 20.0, duration: 4 };
+        assert_eq!(10.0, ramp.value_at(0));
+        assert_eq!(15.0, ramp.value_at(2));
+        assert_eq!(20.0, ramp.value_at(4));
+        assert_eq!(20.0, ramp.value_at(5));
+
+        let periodic = ScheduleShape::Periodic { base: 1.0, amplitude: 0.5, period: 4 };
+        assert_eq!(1.0, periodic.value_at(0));
+        assert_eq!(1.5, periodic.value_at(1));
+        assert!((periodic.value_at(7) - 0.5).abs() < 1e-9);
+
+        let change = ScheduledChange::new("move_cost", 2, step);
+        assert_eq!(None, change.value_at(1));
+        assert_eq!(Some(3.0), change.value_at(2));
+    }
+
+    #[test]
+    fn test_apply_forgets_finished() {
+        let mut schedule = SettingsSchedule::new();
+        let mut settings = Settings::prod();
+        schedule.add(ScheduledChange::new("move_cost", 2, ScheduleShape::Step { value: -9.0 })).unwrap();
+        schedule.add(ScheduledChange::new("night_light", 0, ScheduleShape::Ramp { from: 0.0, to: 1.0, duration: 2 })).unwrap();
+        assert!(schedule.add(ScheduledChange::new("gravity", 0, ScheduleShape::Step { value: 1.0 })).is_err());
+
+        // Night light starts at the value it already has
+        assert!(!schedule.apply(&mut settings, 0));
+        assert_eq!(0.0, settings.night_light());
+        assert!(schedule.apply(&mut settings, 1));
+        assert_eq!(0.5, settings.night_light());
+        assert!(schedule.apply(&mut settings, 2));
+        assert_eq!(1.0, settings.night_light());
+        assert_eq!(-9, settings.move_cost());
+
+        assert!(schedule.changes.is_empty());
+        assert!(!schedule.apply(&mut settings, 3));
+    }
+
+    #[test]
+    fn test_out_of_range_changes() {
+        let mut schedule = SettingsSchedule::new();
+        let periodic = ScheduleShape::Periodic { base: 0.5, amplitude: 0.8, period: 10 };
+        assert!(schedule.add(ScheduledChange::new("mutation_probability", 0, periodic)).is_err());
+        let ramp = ScheduleShape::Ramp { from: 0.0, to: -1.0, duration: 10 };
+        assert!(schedule.add(ScheduledChange::new("night_light", 0, ramp)).is_err());
+        let periodic = ScheduleShape::Periodic { base: 0.5, amplitude: -0.5, period: 10 };
+        assert!(schedule.add(ScheduledChange::new("mutation_probability", 0, periodic)).is_ok());
+        assert_eq!(1, schedule.changes.len());
+    }
+
+    #[test]
+    fn test_apply_reports_only_new_values() {
+        let mut schedule = SettingsSchedule::new();
+        let mut settings = Settings::prod();
+        let periodic = ScheduleShape::Periodic { base: 0.0, amplitude: 0.0, period: 4 };
+        schedule.add(ScheduledChange::new("night_light", 0, periodic)).unwrap();
+        schedule.add(ScheduledChange::new("move_cost", 1, ScheduleShape::Step { value: -9.0 })).unwrap();
+
+        assert!(!schedule.apply(&mut settings, 0));
+        assert!(schedule.apply(&mut settings, 1));
+        for tick in 2..10 {
+            assert!(!schedule.apply(&mut settings, tick));
+        }
+        assert_eq!(1, schedule.changes.len());
+    }
+
+    #[test]
+    fn test_apply_late_changes() {
+        let mut schedule = SettingsSchedule::new();
+        let mut settings = Settings::prod();
+        // Both should have started before the tick they are applied at
+        schedule.add(ScheduledChange::new("move_cost", 2, ScheduleShape::Step { value: -9.0 })).unwrap();
+        schedule.add(ScheduledChange::new("night_light", 0, ScheduleShape::Ramp { from: 0.0, to: 1.0, duration: 2 })).unwrap();
+
+        assert!(schedule.apply(&mut settings, 10));
+        assert_eq!(-9, settings.move_cost());
+        assert_eq!(1.0, settings.night_light());
+        assert!(schedule.changes.is_empty());
+    }
+
+    #[test]
+    fn integration_test_schedule_changes_world_settings() {
+        let mut world = World::new(2, 1, SettingsBuilder::zero());
+        world.schedule.add(ScheduledChange::new("turn_cost", 1, ScheduleShape::Step { value: -3.0 })).unwrap();
+
+        world.tick(&mut Processor::new());
+        assert_eq!(0, world.get_settings().turn_cost());
+
+        world.tick(&mut Processor::new());
+        assert_eq!(-3, world.get_settings().turn_cost());
+        assert_eq!(-3, world.settings_at(1, 0).turn_cost());
+    }
+}
@@ -19,6 +19,107 @@ impl Settings {
             corpse_decay: -2,
             corpse_initial: 20,
             mutation_probability: 0.5,
+            species_distance: GenomeDistance::Hamming,
+            species_threshold: 8,
+            topology: Topology::Torus,
+            neighbourhood: Neighbourhood::Moore,
+            inherit_registers: false,
+            light_depth_falloff: 0.0,
+            day_length: 0,
+            night_light: 0.0,
+            season_length: 0,
+            season_amplitude: 0.0,
+            sunspot_size: 0,
+            sunspot_contrast: 0.0,
+            nutrient_diffusion: 0.05,
+            absorb_adds: 5,
+            absorb_cost: -1,
+            emit_amount: 10.0,
+            emit_cost: -1,
+            smell_cost: -1,
+            signal_diffusion: 0.2,
+            signal_evaporation: 0.1,
+            immigration_rate: 0,
+            reseed_floor: 0,
+            max_lifespan: 0,
+            age_upkeep: 0.0,
+            metabolism_cost: 0,
+            max_health: 0,
+            defend_cost: -2,
+            defend_duration: 3,
+            defend_reduction: 0.5,
+            look_range: 5,
+            look_cost: -1,
+            health_overflow: HealthOverflow::Lost,
+            metabolism_scaling: MetabolismScaling::Flat,
+            brain: BrainKind::Genes,
+        }
+    }
+
+    /// Sets numeric setting by its name. Unknown names and values out of the range
+    /// of the setting are errors, the setting is left as it is then
+    pub fn set_by_name(&mut self, name: &str, value: f64) -> Result<(), String> {
+        let (min, max) = Settings::range_by_name(name);
+        // Written this way to catch NaN too
+        if !(value >= min && value <= max) {
+            return Err(format!("Setting {:?} should be within {}..={}, not {}", name, min, max, value));
+        }
+        match name {
+            "steps_per_turn" => self.steps_per_turn = value as usize,
+            "reproduce_cost" => self.reproduce_cost = value as HealthType,
+            "reproduce_threshold" => self.reproduce_threshold = value as HealthType,
+            "photosynthesis_adds" => self.photosynthesis_adds = value as HealthType,
+            "initial_cell_health" => self.initial_cell_health = value as HealthType,
+            "attack_damage" => self.attack_damage = value as HealthType,
+            "defile_damage" => self.defile_damage = value as HealthType,
+            "attack_cost" => self.attack_cost = value as HealthType,
+            "move_cost" => self.move_cost = value as HealthType,
+            "turn_cost" => self.turn_cost = value as HealthType,
+            "sense_cost" => self.sense_cost = value as HealthType,
+            "defile_cost" => self.defile_cost = value as HealthType,
+            "corpse_decay" => self.corpse_decay = value as HealthType,
+            "corpse_initial" => self.corpse_initial = value as HealthType,
+            "mutation_probability" => self.mutation_probability = value,
+            "species_threshold" => self.species_threshold = value as usize,
+            "light_depth_falloff" => self.light_depth_falloff = value,
+            "day_length" => self.day_length = value as Tick,
+            "night_light" => self.night_light = value,
+            "season_length" => self.season_length = value as Tick,
+            "season_amplitude" => self.season_amplitude = value,
+            "sunspot_size" => self.sunspot_size = value as Coords,
+            "sunspot_contrast" => self.sunspot_contrast = value,
+            "nutrient_diffusion" => self.nutrient_diffusion = value,
+            "absorb_adds" => self.absorb_adds = value as HealthType,
+            "absorb_cost" => self.absorb_cost = value as HealthType,
+            "emit_amount" => self.emit_amount = value,
+            "emit_cost" => self.emit_cost = value as HealthType,
+            "smell_cost" => self.smell_cost = value as HealthType,
+            "signal_diffusion" => self.signal_diffusion = value,
+            "signal_evaporation" => self.signal_evaporation = value,
+            "immigration_rate" => self.immigration_rate = value as usize,
+            "reseed_floor" => self.reseed_floor = value as usize,
+            "max_lifespan" => self.max_lifespan = value as Tick,
+            "age_upkeep" => self.age_upkeep = value,
+            "metabolism_cost" => self.metabolism_cost = value as HealthType,
+            "max_health" => self.max_health = value as HealthType,
+            "defend_cost" => self.defend_cost = value as HealthType,
+            "defend_duration" => self.defend_duration = value as Tick,
+            "defend_reduction" => self.defend_reduction = value,
+            "look_range" => self.look_range = value as Coords,
+            "look_cost" => self.look_cost = value as HealthType,
+            _ => return Err(format!("Unknown setting {:?}", name))
+        }
+        Ok(())
+    }
+
+    // Shares and probabilities are within 0..1, counts and durations could not be negative
+    fn range_by_name(name: &str) -> (f64, f64) {
+        match name {
+            "mutation_probability" | "night_light" | "sunspot_contrast" | "nutrient_diffusion" |
+            "signal_diffusion" | "signal_evaporation" | "defend_reduction" => (0.0, 1.0),
+            "steps_per_turn" | "species_threshold" | "day_length" | "season_length" | "sunspot_size" |
+            "immigration_rate" | "reseed_floor" | "max_lifespan" | "defend_duration" | "look_range" => (0.0, f64::MAX),
+            _ => (f64::MIN, f64::MAX)
         }
     }
 
@@ -37,6 +138,99 @@ impl Settings {
     pub fn corpse_decay(&self) -> HealthType { self.corpse_decay }
     pub fn corpse_initial(&self) -> HealthType { self.corpse_initial }
     pub fn mutation_probability(&self) -> f64 { self.mutation_probability }
+    pub fn species_distance(&self) -> GenomeDistance { self.species_distance }
+    pub fn species_threshold(&self) -> usize { self.species_threshold }
+    pub fn topology(&self) -> Topology { self.topology }
+    pub fn neighbourhood(&self) -> Neighbourhood { self.neighbourhood }
+    pub fn inherit_registers(&self) -> bool { self.inherit_registers }
+    pub fn light_depth_falloff(&self) -> f64 { self.light_depth_falloff }
+    pub fn day_length(&self) -> Tick { self.day_length }
+    pub fn night_light(&self) -> f64 { self.night_light }
+    pub fn season_length(&self) -> Tick { self.season_length }
+    pub fn season_amplitude(&self) -> f64 { self.season_amplitude }
+    pub fn sunspot_size(&self) -> Coords { self.sunspot_size }
+    pub fn sunspot_contrast(&self) -> f64 { self.sunspot_contrast }
+    pub fn nutrient_diffusion(&self) -> f64 { self.nutrient_diffusion }
+    pub fn absorb_adds(&self) -> HealthType { self.absorb_adds }
+    pub fn absorb_cost(&self) -> HealthType { self.absorb_cost }
+    pub fn emit_amount(&self) -> f64 { self.emit_amount }
+    pub fn emit_cost(&self) -> HealthType { self.emit_cost }
+    pub fn smell_cost(&self) -> HealthType { self.smell_cost }
+    pub fn signal_diffusion(&self) -> f64 { self.signal_diffusion }
+    pub fn signal_evaporation(&self) -> f64 { self.signal_evaporation }
+    pub fn immigration_rate(&self) -> usize { self.immigration_rate }
+    pub fn reseed_floor(&self) -> usize { self.reseed_floor }
+    pub fn max_lifespan(&self) -> Tick { self.max_lifespan }
+    pub fn age_upkeep(&self) -> f64 { self.age_upkeep }
+    pub fn metabolism_cost(&self) -> HealthType { self.metabolism_cost }
+    pub fn max_health(&self) -> HealthType { self.max_health }
+    pub fn defend_cost(&self) -> HealthType { self.defend_cost }
+    pub fn defend_duration(&self) -> Tick { self.defend_duration }
+    pub fn defend_reduction(&self) -> f64 { self.defend_reduction }
+    pub fn look_range(&self) -> Coords { self.look_range }
+    pub fn look_cost(&self) -> HealthType { self.look_cost }
+    pub fn health_overflow(&self) -> HealthOverflow { self.health_overflow }
+    pub fn metabolism_scaling(&self) -> MetabolismScaling { self.metabolism_scaling }
+    pub fn brain(&self) -> BrainKind { self.brain }
+}
+
+impl HealthOverflow {
+    pub fn by_name(name: &str) -> Option<HealthOverflow> {
+        match name {
+            "lost" => Some(HealthOverflow::Lost),
+            "reproduce" => Some(HealthOverflow::Reproduce),
+            "share" => Some(HealthOverflow::Share),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HealthOverflow::Lost => "lost",
+            HealthOverflow::Reproduce => "reproduce",
+            HealthOverflow::Share => "share",
+        }
+    }
+}
+
+impl MetabolismScaling {
+    pub fn by_name(name: &str) -> Option<MetabolismScaling> {
+        match name {
+            "flat" => Some(MetabolismScaling::Flat),
+            "genome_length" => Some(MetabolismScaling::GenomeLength),
+            "age" => Some(MetabolismScaling::Age),
+            "health" => Some(MetabolismScaling::Health),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetabolismScaling::Flat => "flat",
+            MetabolismScaling::GenomeLength => "genome_length",
+            MetabolismScaling::Age => "age",
+            MetabolismScaling::Health => "health",
+        }
+    }
+}
+
+impl Topology {
+    pub fn by_name(name: &str) -> Option<Topology> {
+        match name {
+            "torus" => Some(Topology::Torus),
+            "box" => Some(Topology::Box),
+            "cylinder" => Some(Topology::Cylinder),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Torus => "torus",
+            Topology::Box => "box",
+            Topology::Cylinder => "cylinder",
+        }
+    }
 }
 
 impl SettingsBuilder {
@@ -61,6 +255,11 @@ impl SettingsBuilder {
             .with_corpse_decay(0)
             .with_corpse_initial(0)
             .with_mutation_probability(0.0)
+            .with_nutrient_diffusion(0.0)
+            .with_absorb_adds(0)
+            .with_absorb_cost(0)
+            .with_emit_cost(0)
+            .with_smell_cost(0)
             .build()
     }
 
@@ -124,6 +323,142 @@ impl SettingsBuilder {
         self.settings.mutation_probability = value; self
     }
 
+    pub fn with_species_distance(&mut self, value: GenomeDistance) -> &mut SettingsBuilder {
+        self.settings.species_distance = value; self
+    }
+
+    pub fn with_species_threshold(&mut self, value: usize) -> &mut SettingsBuilder {
+        self.settings.species_threshold = value; self
+    }
+
+    pub fn with_topology(&mut self, value: Topology) -> &mut SettingsBuilder {
+        self.settings.topology = value; self
+    }
+
+    pub fn with_neighbourhood(&mut self, value: Neighbourhood) -> &mut SettingsBuilder {
+        self.settings.neighbourhood = value; self
+    }
+
+    pub fn with_inherit_registers(&mut self, value: bool) -> &mut SettingsBuilder {
+        self.settings.inherit_registers = value; self
+    }
+
+    pub fn with_light_depth_falloff(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.light_depth_falloff = value; self
+    }
+
+    pub fn with_day_length(&mut self, value: Tick) -> &mut SettingsBuilder {
+        self.settings.day_length = value; self
+    }
+
+    pub fn with_night_light(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.night_light = value; self
+    }
+
+    pub fn with_season_length(&mut self, value: Tick) -> &mut SettingsBuilder {
+        self.settings.season_length = value; self
+    }
+
+    pub fn with_season_amplitude(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.season_amplitude = value; self
+    }
+
+    pub fn with_sunspot_size(&mut self, value: Coords) -> &mut SettingsBuilder {
+        self.settings.sunspot_size = value; self
+    }
+
+    pub fn with_sunspot_contrast(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.sunspot_contrast = value; self
+    }
+
+    pub fn with_nutrient_diffusion(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.nutrient_diffusion = value; self
+    }
+
+    pub fn with_absorb_adds(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.absorb_adds = value; self
+    }
+
+    pub fn with_absorb_cost(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.absorb_cost = value; self
+    }
+
+    pub fn with_emit_amount(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.emit_amount = value; self
+    }
+
+    pub fn with_emit_cost(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.emit_cost = value; self
+    }
+
+    pub fn with_smell_cost(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.smell_cost = value; self
+    }
+
+    pub fn with_signal_diffusion(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.signal_diffusion = value; self
+    }
+
+    pub fn with_signal_evaporation(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.signal_evaporation = value; self
+    }
+
+    pub fn with_immigration_rate(&mut self, value: usize) -> &mut SettingsBuilder {
+        self.settings.immigration_rate = value; self
+    }
+
+    pub fn with_reseed_floor(&mut self, value: usize) -> &mut SettingsBuilder {
+        self.settings.reseed_floor = value; self
+    }
+
+    pub fn with_max_lifespan(&mut self, value: Tick) -> &mut SettingsBuilder {
+        self.settings.max_lifespan = value; self
+    }
+
+    pub fn with_age_upkeep(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.age_upkeep = value; self
+    }
+
+    pub fn with_metabolism_cost(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.metabolism_cost = value; self
+    }
+
+    pub fn with_max_health(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.max_health = value; self
+    }
+
+    pub fn with_defend_cost(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.defend_cost = value; self
+    }
+
+    pub fn with_defend_duration(&mut self, value: Tick) -> &mut SettingsBuilder {
+        self.settings.defend_duration = value; self
+    }
+
+    pub fn with_defend_reduction(&mut self, value: f64) -> &mut SettingsBuilder {
+        self.settings.defend_reduction = value; self
+    }
+
+    pub fn with_look_range(&mut self, value: Coords) -> &mut SettingsBuilder {
+        self.settings.look_range = value; self
+    }
+
+    pub fn with_look_cost(&mut self, value: HealthType) -> &mut SettingsBuilder {
+        self.settings.look_cost = value; self
+    }
+
+    pub fn with_health_overflow(&mut self, value: HealthOverflow) -> &mut SettingsBuilder {
+        self.settings.health_overflow = value; self
+    }
+
+    pub fn with_metabolism_scaling(&mut self, value: MetabolismScaling) -> &mut SettingsBuilder {
+        self.settings.metabolism_scaling = value; self
+    }
+
+    pub fn with_brain(&mut self, value: BrainKind) -> &mut SettingsBuilder {
+        self.settings.brain = value; self
+    }
+
     // TODO: make it consume itself
     pub fn build(&mut self) -> Settings {
         self.settings.clone()
@@ -134,6 +469,20 @@ impl SettingsBuilder {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_set_by_name() {
+        let mut settings = Settings::prod();
+        assert!(settings.set_by_name("move_cost", -20.0).is_ok());
+        assert!(settings.set_by_name("mutation_probability", 0.9).is_ok());
+        assert!(settings.set_by_name("gravity", 9.8).is_err());
+        assert!(settings.set_by_name("mutation_probability", 1.5).is_err());
+        assert!(settings.set_by_name("mutation_probability", f64::NAN).is_err());
+        assert!(settings.set_by_name("day_length", -1.0).is_err());
+
+        assert_eq!(-20, settings.move_cost());
+        assert_eq!(0.9, settings.mutation_probability());
+    }
+
     #[test]
     fn test_builder() {
         let settings = SettingsBuilder::prod()
@@ -150,6 +499,40 @@ mod tests {
             .with_corpse_initial(11)
             .with_defile_cost(12)
             .with_mutation_probability(0.13)
+            .with_species_distance(GenomeDistance::Edit)
+            .with_species_threshold(14)
+            .with_topology(Topology::Cylinder)
+            .with_neighbourhood(Neighbourhood::Hex)
+            .with_inherit_registers(true)
+            .with_light_depth_falloff(0.15)
+            .with_day_length(16)
+            .with_night_light(0.17)
+            .with_season_length(18)
+            .with_season_amplitude(0.19)
+            .with_sunspot_size(20)
+            .with_sunspot_contrast(0.21)
+            .with_nutrient_diffusion(0.22)
+            .with_absorb_adds(23)
+            .with_absorb_cost(24)
+            .with_emit_amount(25.0)
+            .with_emit_cost(26)
+            .with_smell_cost(27)
+            .with_signal_diffusion(0.28)
+            .with_signal_evaporation(0.29)
+            .with_immigration_rate(30)
+            .with_reseed_floor(31)
+            .with_max_lifespan(32)
+            .with_age_upkeep(0.33)
+            .with_metabolism_cost(34)
+            .with_max_health(35)
+            .with_defend_cost(36)
+            .with_defend_duration(37)
+            .with_defend_reduction(0.38)
+            .with_look_range(39)
+            .with_look_cost(40)
+            .with_health_overflow(HealthOverflow::Share)
+            .with_metabolism_scaling(MetabolismScaling::Age)
+            .with_brain(BrainKind::Neural)
             .build();
 
         assert_eq!(1, settings.reproduce_cost());
@@ -165,5 +548,39 @@ mod tests {
         assert_eq!(11, settings.corpse_initial());
         assert_eq!(12, settings.defile_cost());
         assert_eq!(0.13, settings.mutation_probability());
+        assert_eq!(GenomeDistance::Edit, settings.species_distance());
+        assert_eq!(14, settings.species_threshold());
+        assert_eq!(Topology::Cylinder, settings.topology());
+        assert_eq!(Neighbourhood::Hex, settings.neighbourhood());
+        assert!(settings.inherit_registers());
+        assert_eq!(0.15, settings.light_depth_falloff());
+        assert_eq!(16, settings.day_length());
+        assert_eq!(0.17, settings.night_light());
+        assert_eq!(18, settings.season_length());
+        assert_eq!(0.19, settings.season_amplitude());
+        assert_eq!(20, settings.sunspot_size());
+        assert_eq!(0.21, settings.sunspot_contrast());
+        assert_eq!(0.22, settings.nutrient_diffusion());
+        assert_eq!(23, settings.absorb_adds());
+        assert_eq!(24, settings.absorb_cost());
+        assert_eq!(25.0, settings.emit_amount());
+        assert_eq!(26, settings.emit_cost());
+        assert_eq!(27, settings.smell_cost());
+        assert_eq!(0.28, settings.signal_diffusion());
+        assert_eq!(0.29, settings.signal_evaporation());
+        assert_eq!(30, settings.immigration_rate());
+        assert_eq!(31, settings.reseed_floor());
+        assert_eq!(32, settings.max_lifespan());
+        assert_eq!(0.33, settings.age_upkeep());
+        assert_eq!(34, settings.metabolism_cost());
+        assert_eq!(35, settings.max_health());
+        assert_eq!(36, settings.defend_cost());
+        assert_eq!(37, settings.defend_duration());
+        assert_eq!(0.38, settings.defend_reduction());
+        assert_eq!(39, settings.look_range());
+        assert_eq!(40, settings.look_cost());
+        assert_eq!(HealthOverflow::Share, settings.health_overflow());
+        assert_eq!(MetabolismScaling::Age, settings.metabolism_scaling());
+        assert_eq!(BrainKind::Neural, settings.brain());
     }
 }
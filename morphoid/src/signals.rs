@@ -0,0 +1,89 @@
+use crate::types::*;
+
+impl World {
+    /// Nothing could be smelled behind the edge of the world
+    pub fn signal(&self, x: Coords, y: Coords, channel: usize) -> f64 {
+        match self.resolve(x, y) {
+            Some((x, y)) => self.signals[channel][self.get_index(x, y)],
+            None => 0.0
+        }
+    }
+
+    pub fn add_signal(&mut self, x: Coords, y: Coords, channel: usize, amount: f64) {
+        let index = self.get_index(x, y);
+        self.signals[channel][index] += amount;
+    }
+
+    pub fn spread_signals(&mut self) {
+        let diffusion = self.settings.signal_diffusion();
+        let remains = 1.0 - self.settings.signal_evaporation();
+
+        for channel in 0..SIGNAL_CHANNELS {
+            let mut field = if diffusion > 0.0 {
+                self.diffuse(&self.signals[channel], diffusion)
+            } else {
+                self.signals[channel].clone()
+            };
+            for value in field.iter_mut() {
+                *value *= remains;
+            }
+            self.signals[channel] = field;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spread_signals() {
+        let settings = SettingsBuilder::prod()
+            .with_signal_diffusion(0.4)
+            .with_signal_evaporation(0.5)
+            .build();
+        let mut world = World::new(3, 3, settings);
+        world.add_signal(1, 1, 1, 10.0);
+
+        world.spread_signals();
+
+        assert_eq!(3.0, world.signal(1, 1, 1));
+        assert_eq!(0.5, world.signal(1, 0, 1));
+        assert_eq!(0.0, world.signal(1, 1, 0));
+    }
+
+    #[test]
+    fn integration_test_emit() {
+        let settings = SettingsBuilder::prod()
+            .with_emit_amount(4.0)
+            .with_signal_diffusion(0.0)
+            .with_signal_evaporation(0.0)
+            .build();
+        let mut world = World::new(1, 1, settings);
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, EMIT);
+        genome.mutate(1, 3);
+        world.set_cell(0, 0, genome);
+
+        world.tick(&mut Processor::new());
+
+        assert_eq!(0.0, world.signal(0, 0, 0));
+        assert_eq!(4.0, world.signal(0, 0, 1));
+    }
+
+    #[test]
+    fn integration_test_smell_turns_to_gradient() {
+        let settings = SettingsBuilder::zero();
+        let mut world = World::new(3, 3, settings);
+        let mut genome = Genome::new_plant();
+        genome.mutate(0, SMELL);
+        genome.mutate(1, 0);
+        world.set_cell_ext(1, 1, genome, Direction::North);
+        world.add_signal(2, 2, 0, 1.0);
+        world.add_signal(0, 1, 0, 2.0);
+
+        world.tick(&mut Processor::new());
+
+        assert_eq!(Direction::West, world.get_state_by_pos(1, 1).unwrap().direction);
+    }
+}
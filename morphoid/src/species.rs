@@ -0,0 +1,204 @@
+use std::cmp;
+use std::collections::HashMap;
+
+use crate::types::*;
+
+impl GenomeDistance {
+    pub fn by_name(name: &str) -> Option<GenomeDistance> {
+        match name {
+            "hamming" => Some(GenomeDistance::Hamming),
+            "edit" => Some(GenomeDistance::Edit),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenomeDistance::Hamming => "hamming",
+            GenomeDistance::Edit => "edit",
+        }
+    }
+
+    pub fn between(&self, first: &[Gene], second: &[Gene]) -> usize {
+        match self {
+            GenomeDistance::Hamming => {
+                let different = first.iter()
+                    .zip(second.iter())
+                    .filter(|(a, b)| a != b)
+                    .count();
+                different + cmp::max(first.len(), second.len()) - cmp::min(first.len(), second.len())
+            },
+            GenomeDistance::Edit => {
+                // Levenshtein, keeping only the previous row
+                let mut previous: Vec<usize> = (0..=second.len()).collect();
+                let mut current = vec![0; second.len() + 1];
+
+                for (i, a) in first.iter().enumerate() {
+                    current[0] = i + 1;
+                    for (j, b) in second.iter().enumerate() {
+                        let substitution = previous[j] + if a == b { 0 } else { 1 };
+                        current[j + 1] = cmp::min(substitution, cmp::min(previous[j + 1], current[j]) + 1);
+                    }
+                    std::mem::swap(&mut previous, &mut current);
+                }
+                previous[second.len()]
+            }
+        }
+    }
+}
+
+impl SpeciesTracker {
+    pub fn new() -> SpeciesTracker {
+        SpeciesTracker {
+            species: Vec::new(),
+            assignments: HashMap::new()
+        }
+    }
+
+    /// Assigns species to new genomes, forgets dead ones and recalculates sizes
+    pub fn update(&mut self, genomes: &GenomeStorage, settings: &Settings, tick: Tick) {
+        let distance = settings.species_distance();
+        let threshold = settings.species_threshold();
+
+        let mut newcomers: Vec<&Genome> = genomes.genomes
+            .values()
+            .filter(|genome| !self.assignments.contains_key(&genome.id()))
+            .collect();
+        // Older first, so parents are classified before children
+        newcomers.sort_by_key(|genome| genome.id());
+
+        for genome in newcomers {
+            let parent_species = genome.parent_id()
+                .and_then(|parent_id| self.assignments.get(&parent_id))
+                .cloned();
+            let species_id = self.classify(genome, parent_species, distance, threshold, tick);
+            self.assignments.insert(genome.id(), species_id);
+        }
+
+        self.assignments.retain(|genome_id, _| genomes.get(*genome_id).is_some());
+
+        let mut sizes: HashMap<SpeciesId, usize> = HashMap::new();
+        for species_id in self.assignments.values() {
+            *sizes.entry(*species_id).or_insert(0) += 1;
+        }
+
+        for species in self.species.iter_mut() {
+            species.size = *sizes.get(&species.id).unwrap_or(&0);
+            if species.size == 0 && species.extinction_tick.is_none() {
+                species.extinction_tick = Some(tick);
+            }
+        }
+    }
+
+    pub fn species_of(&self, genome_id: GenomeId) -> Option<SpeciesId> {
+        self.assignments.get(&genome_id).cloned()
+    }
+
+    pub fn get(&self, species_id: SpeciesId) -> Option<&Species> {
+        self.species.iter().find(|species| species.id == species_id)
+    }
+
+    pub fn living(&self) -> Vec<&Species> {
+        self.species
+            .iter()
+            .filter(|species| species.extinction_tick.is_none())
+            .collect()
+    }
+
+    fn classify(&mut self, genome: &Genome, parent_species: Option<SpeciesId>,
+                distance: GenomeDistance, threshold: usize, tick: Tick) -> SpeciesId {
+        let is_close = |species: &Species| {
+            distance.between(&species.representative, &genome.genes[..]) <= threshold
+        };
+
+        if let Some(species) = parent_species.and_then(|id| self.get(id)) {
+            if is_close(species) {
+                return species.id;
+            }
+        }
+
+        let found = self.species
+            .iter()
+            .filter(|species| species.extinction_tick.is_none())
+            .find(|species| is_close(species))
+            .map(|species| species.id);
+
+        found.unwrap_or_else(|| {
+            let id = self.species.len() as SpeciesId;
+            self.species.push(Species {
+                id,
+                representative: *genome.genes,
+                size: 0,
+                birth_tick: tick,
+                extinction_tick: None
+            });
+            id
+        })
+    }
+}
+
+impl Default for SpeciesTracker {
+    fn default() -> SpeciesTracker {
+        SpeciesTracker::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let hamming = GenomeDistance::Hamming;
+        assert_eq!(0, hamming.between(&[1, 2, 3], &[1, 2, 3]));
+        assert_eq!(2, hamming.between(&[1, 2, 3], &[1, 3, 2]));
+        assert_eq!(3, hamming.between(&[1, 2, 3], &[2, 3]));
+
+        let edit = GenomeDistance::Edit;
+        assert_eq!(0, edit.between(&[1, 2, 3], &[1, 2, 3]));
+        assert_eq!(1, edit.between(&[1, 2, 3], &[2, 3]));
+        assert_eq!(2, edit.between(&[1, 2, 3, 4], &[2, 3, 4, 5]));
+        assert_eq!(3, edit.between(&[], &[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_update() {
+        let settings = SettingsBuilder::prod()
+            .with_species_distance(GenomeDistance::Hamming)
+            .with_species_threshold(2)
+            .build();
+
+        let mut storage = GenomeStorage::new();
+        let plant = Genome::new_plant();
+        let mut close_plant = plant.new_child();
+        close_plant.mutate(0, ATTACK);
+        let predator = Genome::new_predator();
+        let (plant_id, close_id, predator_id) = (plant.id(), close_plant.id(), predator.id());
+
+        storage.put(plant);
+        storage.put(close_plant);
+        storage.put(predator);
+
+        let mut tracker = SpeciesTracker::new();
+        tracker.update(&storage, &settings, 1);
+
+        assert_eq!(2, tracker.living().len());
+        assert_eq!(tracker.species_of(plant_id), tracker.species_of(close_id));
+        assert_ne!(tracker.species_of(plant_id), tracker.species_of(predator_id));
+
+        let plants = tracker.species_of(plant_id).unwrap();
+        assert_eq!(2, tracker.get(plants).unwrap().size);
+        assert_eq!(1, tracker.get(plants).unwrap().birth_tick);
+
+        // Same ids next tick, predators die out
+        storage.remove(predator_id);
+        tracker.update(&storage, &settings, 2);
+
+        assert_eq!(Some(plants), tracker.species_of(close_id));
+        assert_eq!(1, tracker.living().len());
+
+        let predators = tracker.species.iter().find(|s| s.id != plants).unwrap();
+        assert_eq!(Some(2), predators.extinction_tick);
+        assert_eq!(0, predators.size);
+    }
+}
@@ -1,14 +1,22 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+
+use rand::RngCore;
 
 pub type Coords = i32;
 pub type GenomeId = u64;
+pub type GenomeHash = u64;
 pub type Gene = usize;
 pub type HealthType = i32;
 pub type GeneIndex = usize; // TODO: rename in other places
+pub type Tick = u64;
+pub type SpeciesId = u64;
 
 pub const GENOME_LENGTH: usize = 64;
 pub const GENE_COUNT: usize = 64;
 
+pub type Genes = [Gene; GENOME_LENGTH];
+
 // TODO: convert it to enums
 pub const DEFILE: Gene = 25;
 pub const SENSE: Gene = 26;
@@ -19,22 +27,60 @@ pub const MOVE: Gene = 28;
 pub const ATTACK: Gene = 29;
 pub const REPRODUCE: Gene = 30;
 pub const PHOTOSYNTHESIS: Gene = 31;
+pub const ABSORB: Gene = 32;
+// Complex gene
+pub const EMIT: Gene = 33;
+// Complex gene
+pub const SMELL: Gene = 34;
+pub const DEFEND: Gene = 35;
+// Complex genes, act toward relative direction without turning
+pub const ATTACK_TOWARDS: Gene = 36;
+pub const MOVE_TOWARDS: Gene = 37;
+pub const REPRODUCE_TOWARDS: Gene = 38;
+pub const SENSE_TOWARDS: Gene = 39;
+// Sees along the direction, up to look_range squares
+pub const LOOK: Gene = 40;
+// Complex genes working with registers, operand is the register
+// Senses like SENSE and stores the branch number
+pub const STORE_SENSE: Gene = 41;
+pub const INCREMENT: Gene = 42;
+pub const DECREMENT: Gene = 43;
+// Next gene after the operand if the register is zero, the one after it otherwise
+pub const IF_ZERO: Gene = 44;
 
-pub const KNOWN_GENES: [Gene; 7] = [
+pub const SIGNAL_CHANNELS: usize = 2;
+
+pub const REGISTERS: usize = 4;
+pub type Registers = [i32; REGISTERS];
+
+pub const KNOWN_GENES: [Gene; 20] = [
     DEFILE,
     SENSE,
     TURN,
     MOVE,
     ATTACK,
     REPRODUCE,
-    PHOTOSYNTHESIS
+    PHOTOSYNTHESIS,
+    ABSORB,
+    EMIT,
+    SMELL,
+    DEFEND,
+    ATTACK_TOWARDS,
+    MOVE_TOWARDS,
+    REPRODUCE_TOWARDS,
+    SENSE_TOWARDS,
+    LOOK,
+    STORE_SENSE,
+    INCREMENT,
+    DECREMENT,
+    IF_ZERO,
 ];
 
 pub struct SettingsBuilder {
     pub settings: Settings
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Settings {
     pub steps_per_turn: usize,
     pub reproduce_cost: HealthType,
@@ -51,6 +97,163 @@ pub struct Settings {
     pub corpse_decay: HealthType,
     pub corpse_initial: HealthType,
     pub mutation_probability: f64,
+    pub species_distance: GenomeDistance,
+    pub species_threshold: usize,
+    pub topology: Topology,
+    pub neighbourhood: Neighbourhood,
+    // Child starts with registers of the parent, or with zeros
+    pub inherit_registers: bool,
+    // 0 means same light at any depth, 1 means total darkness at the bottom
+    pub light_depth_falloff: f64,
+    // 0 means there is no night
+    pub day_length: Tick,
+    pub night_light: f64,
+    // 0 means there are no seasons
+    pub season_length: Tick,
+    pub season_amplitude: f64,
+    // 0 means there are no sunspots
+    pub sunspot_size: Coords,
+    pub sunspot_contrast: f64,
+    // Share of nutrients spreading to neighbour squares every tick
+    pub nutrient_diffusion: f64,
+    pub absorb_adds: HealthType,
+    pub absorb_cost: HealthType,
+    pub emit_amount: f64,
+    pub emit_cost: HealthType,
+    pub smell_cost: HealthType,
+    pub signal_diffusion: f64,
+    // Share of signal disappearing every tick
+    pub signal_evaporation: f64,
+    // Random organisms spawned on empty squares every tick
+    pub immigration_rate: usize,
+    // World is reseeded up to this population, 0 disables it
+    pub reseed_floor: usize,
+    // Cells die of old age after that many ticks, 0 means they live forever
+    pub max_lifespan: Tick,
+    // Health paid every tick for every tick of age
+    pub age_upkeep: f64,
+    // Paid by every cell every tick, whatever it does
+    pub metabolism_cost: HealthType,
+    // Health above it is overflow, 0 means no limit
+    pub max_health: HealthType,
+    pub defend_cost: HealthType,
+    // Ticks the cell stays hardened after DEFEND
+    pub defend_duration: Tick,
+    // Share of attack damage blocked by hardened cell
+    pub defend_reduction: f64,
+    // Squares LOOK gene could see
+    pub look_range: Coords,
+    // Paid for every square LOOK has passed
+    pub look_cost: HealthType,
+    pub health_overflow: HealthOverflow,
+    pub metabolism_scaling: MetabolismScaling,
+    pub brain: BrainKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegionArea {
+    Rect { x: Coords, y: Coords, width: Coords, height: Coords },
+    // Rows of the world, true if square belongs to the region
+    Mask(Vec<Vec<bool>>),
+}
+
+/// Part of the world with some of the settings overridden by name
+#[derive(Clone, Debug)]
+pub struct Region {
+    pub name: String,
+    pub area: RegionArea,
+    pub overrides: Vec<(String, f64)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduleShape {
+    // Set once at the start tick
+    Step { value: f64 },
+    // Changes linearly during the duration, stays at `to` after that
+    Ramp { from: f64, to: f64, duration: Tick },
+    // Oscillates around the base forever
+    Periodic { base: f64, amplitude: f64, period: Tick },
+}
+
+/// Change of one numeric setting in time, starting at the tick
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledChange {
+    pub setting: String,
+    pub start_tick: Tick,
+    pub shape: ScheduleShape,
+}
+
+#[derive(Clone, Debug)]
+pub struct SettingsSchedule {
+    pub changes: Vec<ScheduledChange>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventKind {
+    // Cells in a rectangle at random place become corpses
+    Wipe { width: Coords, height: Coords },
+    // Share of all cells is killed at random
+    Cull { share: f64 },
+    // Mutation probability is replaced for the duration, then restored
+    MutationSpike { probability: f64, duration: Tick },
+    // Light comes from the bottom instead of the top, or back
+    FlipLight,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventRecord {
+    pub tick: Tick,
+    pub kind: EventKind,
+    pub killed: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventQueue {
+    // Sorted by tick
+    pub pending: Vec<(Tick, EventKind)>,
+    pub log: Vec<EventRecord>,
+    // Mutation spikes going on, end tick and probability, the latest one is in effect.
+    // They are laid over the settings, which could change meanwhile
+    pub spikes: Vec<(Tick, f64)>,
+}
+
+// What happens to health above the maximum
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthOverflow {
+    Lost,
+    // Cell reproduces, the child gets the excess as its health
+    Reproduce,
+    // Split between the neighbour cells, up to their maximum
+    Share,
+}
+
+// What the metabolism cost is multiplied by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetabolismScaling {
+    Flat,
+    // Share of genes doing something, genome length itself is fixed
+    GenomeLength,
+    // Cost doubles every AGE_METABOLISM_PERIOD ticks of age
+    Age,
+    // Health relative to the initial health of a cell
+    Health,
+}
+
+pub const AGE_METABOLISM_PERIOD: Tick = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    Torus,
+    // Walls on every side
+    Box,
+    // Wraps horizontally, walls at the top and the bottom
+    Cylinder,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenomeDistance {
+    Hamming,
+    Edit,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -58,6 +261,36 @@ pub enum Entity {
     Nothing,
     Cell(GenomeId),
     Corpse(HealthType),
+    // Rock, nothing could enter it
+    Wall,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    Starvation,
+    Attack,
+    // Removed from outside, by api or kill action
+    Killed,
+    Catastrophe,
+    OldAge,
+}
+
+// Directions a cell could face and act toward
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighbourhood {
+    VonNeumann,
+    Moore,
+    // Odd rows are shifted half a square to the east
+    Hex,
+}
+
+// How genes of a genome are read, the same genes make different cells for every kind
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrainKind {
+    // Genes are instructions run by the processor
+    Genes,
+    // Genes are weights of a small network with fixed topology
+    Neural,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -76,21 +309,49 @@ pub enum Direction {
 pub struct CellState {
     pub health: HealthType,
     pub direction: Direction,
+    // Ticks since birth
+    pub age: Tick,
+    // Ticks left until the cell is not hardened anymore
+    pub defended_for: Tick,
+    pub registers: Registers,
 }
 
 pub struct CellStateStorage {
     pub states: HashMap<GenomeId, CellState>
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mutation {
+    pub index: GeneIndex,
+    pub old_gene: Gene,
+    pub new_gene: Gene,
+}
+
+/// Identity of a single cell, genes are shared between all cells having the same content
+#[derive(Clone)]
 pub struct Genome {
     pub id: GenomeId,
-    pub genes: [Gene; GENOME_LENGTH],
+    pub hash: GenomeHash,
+    pub genes: Arc<Genes>,
+    pub parent_id: Option<GenomeId>,
+    pub birth_tick: Tick,
+    pub mutations: Vec<Mutation>,
 }
 
 pub struct GenomeState {
     pub current_gene: GeneIndex,
 }
 
+// Field of the genome description a gene is counted in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneCategory {
+    Reproduces,
+    Attacks,
+    Photosynthesis,
+    Defiles,
+    Other,
+}
+
 pub struct GenomeDesc {
     pub reproduces: usize,
     pub attacks: usize,
@@ -98,22 +359,124 @@ pub struct GenomeDesc {
     pub defiles: usize
 }
 
+pub struct SharedGenes {
+    pub genes: Arc<Genes>,
+    pub hash: GenomeHash,
+    pub desc: GenomeDesc,
+    pub cells: usize,
+}
+
 // TODO: move to processor
 pub struct GenomeStorage {
     pub genomes: HashMap<GenomeId, Genome>,
-    pub descriptors: HashMap<GenomeId, GenomeDesc>,
+    // Keyed by the genes themselves, different genes could have the same hash
+    pub shared: HashMap<Arc<Genes>, SharedGenes>,
+}
+
+#[derive(Debug)]
+pub struct LineageRecord {
+    pub parent_id: Option<GenomeId>,
+    pub birth_tick: Tick,
+    pub death_tick: Option<Tick>,
+    pub mutations: Vec<Mutation>,
+    pub children: Vec<GenomeId>,
+    pub category: char,
+}
+
+/// Keeps records of dead genomes only while they have living descendants
+pub struct LineageStorage {
+    pub records: HashMap<GenomeId, LineageRecord>,
+}
+
+/// Node of pruned phylogenetic tree, chains of dead ancestors are collapsed
+#[derive(Debug)]
+pub struct PhyloNode {
+    pub genome_id: GenomeId,
+    pub alive: bool,
+    pub branch_length: Tick,
+    pub mutations: Vec<Mutation>,
+    pub population: usize,
+    pub category: char,
+    pub children: Vec<PhyloNode>,
+}
+
+pub struct Phylogeny {
+    pub roots: Vec<PhyloNode>,
+}
+
+#[derive(Debug)]
+pub struct Species {
+    pub id: SpeciesId,
+    pub representative: Genes,
+    pub size: usize,
+    pub birth_tick: Tick,
+    pub extinction_tick: Option<Tick>,
+}
+
+/// Groups living genomes around representatives of the species,
+/// genome keeps its species for the whole life
+pub struct SpeciesTracker {
+    pub species: Vec<Species>,
+    pub assignments: HashMap<GenomeId, SpeciesId>,
+}
+
+#[derive(Debug)]
+pub struct GenotypeRecord {
+    pub hash: GenomeHash,
+    pub genes: Genes,
+    pub first_seen_tick: Tick,
+    pub last_seen_tick: Tick,
+    pub population: usize,
+    pub peak_population: usize,
+    pub total_offspring: usize,
+    pub deaths: HashMap<DeathCause, usize>,
+}
+
+/// Every distinct genome content the world has ever produced.
+/// Keyed by the genes themselves, different genes could have the same hash
+pub struct GenomeArchive {
+    pub records: HashMap<Arc<Genes>, GenotypeRecord>,
+}
+
+/// Genes defined outside of morphoid, by their opcodes
+pub struct GeneRegistry {
+    pub handlers: HashMap<Gene, Box<dyn GeneHandler>>,
 }
 
 pub struct Processor {
-    pub genome_states: HashMap<GenomeId, GenomeState>
+    pub genome_states: HashMap<GenomeId, GenomeState>,
 }
 
+// Reads what is around the cell, has no state between turns
+pub struct NeuralBrain;
+
 pub struct World {
     pub width: Coords,
     pub height: Coords,
     pub settings: Settings,
+    pub regions: Vec<Region>,
+    // Global settings with overrides of every region applied
+    pub region_settings: Vec<Settings>,
+    // Index of the region for every square, the last added region wins
+    pub region_map: Vec<Option<usize>>,
+    pub schedule: SettingsSchedule,
+    pub events: EventQueue,
+    pub light_flipped: bool,
+    pub tick_no: Tick,
     pub entities: Vec<Entity>,
+    // Released by decaying corpses, absorbed by cells
+    pub nutrients: Vec<f64>,
+    // One field per signal channel
+    pub signals: Vec<Vec<f64>>,
     pub genomes: GenomeStorage,
+    pub lineage: LineageStorage,
+    pub species: SpeciesTracker,
+    pub archive: GenomeArchive,
+    pub gene_registry: GeneRegistry,
+    // Told about everything happening, in the order they were added
+    pub observers: Vec<Box<dyn WorldObserver>>,
+    // Thinks for the cells, None means the gene machine of the processor
+    pub brain: Option<Box<dyn Brain>>,
     // TODO: move to processor
     pub cell_states: CellStateStorage,
 }
@@ -125,25 +488,90 @@ pub trait Affector {
     fn set_corpse(&mut self, x: Coords, y: Coords, value: HealthType);
     fn set_entity(&mut self, x: Coords, y: Coords, entity: Entity, genome: Option<Genome>, initial_state: Option<CellState>);
 
-    fn move_cell(&mut self, x: Coords, y: Coords);
+    fn move_cell(&mut self, x: Coords, y: Coords) {
+        self.move_cell_towards(x, y, 0);
+    }
+    fn move_cell_towards(&mut self, x: Coords, y: Coords, rotation: Gene);
     fn rotate_cell(&mut self, x: Coords, y: Coords, value: Gene);
 
     fn punish_for_action(&mut self, x: Coords, y: Coords, gene: Gene);
     fn update_health(&mut self, x: Coords, y: Coords, health_delta: HealthType) -> HealthType;
-    fn attack(&mut self, x: Coords, y: Coords, damage: HealthType);
+    fn update_health_ext(&mut self, x: Coords, y: Coords, health_delta: HealthType, cause: DeathCause) -> HealthType;
+    fn attack(&mut self, x: Coords, y: Coords, damage: HealthType) {
+        self.attack_towards(x, y, 0, damage);
+    }
+    fn attack_towards(&mut self, x: Coords, y: Coords, rotation: Gene, damage: HealthType);
     fn defile(&mut self, x: Coords, y: Coords, damage: HealthType);
-    fn reproduce(&mut self, x: Coords, y: Coords);
+    fn reproduce(&mut self, x: Coords, y: Coords) {
+        self.reproduce_towards(x, y, 0);
+    }
+    fn reproduce_towards(&mut self, x: Coords, y: Coords, rotation: Gene);
     fn decay(&mut self, x: Coords, y: Coords, decay: HealthType);
+    fn absorb(&mut self, x: Coords, y: Coords, amount: HealthType);
+    fn emit(&mut self, x: Coords, y: Coords, channel: usize, amount: f64);
+    fn defend(&mut self, x: Coords, y: Coords, duration: Tick);
+    fn set_registers(&mut self, x: Coords, y: Coords, registers: Registers);
 
     fn build_child_genome_for(&self, parent_genome_id: GenomeId) -> Option<Genome>;
 }
 
+/// Gets told what happens in the world, every callback does nothing by default
+pub trait WorldObserver: Send {
+    fn tick_started(&mut self, _tick: Tick) {}
+    fn tick_finished(&mut self, _tick: Tick) {}
+    fn cell_born(&mut self, _x: Coords, _y: Coords, _genome: &Genome) {}
+    fn cell_died(&mut self, _x: Coords, _y: Coords, _genome_id: GenomeId, _cause: DeathCause) {}
+    fn cell_moved(&mut self, _genome_id: GenomeId, _from: (Coords, Coords), _to: (Coords, Coords)) {}
+    // Damage is what is left after the defence of the target
+    fn attack_landed(&mut self, _attacker: (Coords, Coords), _target: (Coords, Coords), _damage: HealthType) {}
+    // Remains are 0 if the corpse is gone
+    fn corpse_decayed(&mut self, _x: Coords, _y: Coords, _remains: HealthType) {}
+    // Global settings, regions could override some of them
+    fn settings_changed(&mut self, _settings: &Settings) {}
+}
+
 pub trait Perceptor {
     fn get_entity(&self, x: Coords, y: Coords) -> &Entity;
     fn get_state(&self, genome_id: GenomeId) -> &CellState;
     fn get_state_by_pos(&self, x: Coords, y: Coords) -> Option<&CellState>;
     fn get_genome(&self, genome_id: GenomeId) -> Option<&Genome>;
-    fn looking_at(&self, x: Coords, y: Coords) -> Option<(Coords, Coords)>;
+    fn looking_at(&self, x: Coords, y: Coords) -> Option<(Coords, Coords)> {
+        self.looking_towards(x, y, 0)
+    }
+    // Rotation is relative to the direction of the cell
+    fn looking_towards(&self, x: Coords, y: Coords, rotation: Gene) -> Option<(Coords, Coords)>;
+    // None if the square is behind the edge of the world
+    fn resolve(&self, x: Coords, y: Coords) -> Option<(Coords, Coords)>;
+    fn light_at(&self, x: Coords, y: Coords) -> f64;
+    fn signal_at(&self, x: Coords, y: Coords, channel: usize) -> f64;
+    fn gene_registry(&self) -> &GeneRegistry;
+}
+
+/// Custom gene, the processor runs it instead of the goto its opcode would be
+pub trait GeneHandler: Send + Sync {
+    fn opcode(&self) -> Gene;
+    // Number of genes after it read as operands
+    fn operands(&self) -> usize { 0 }
+    // Paid every time the gene runs, negative like the other costs
+    fn cost(&self, _settings: &Settings) -> HealthType { 0 }
+    fn category(&self) -> GeneCategory { GeneCategory::Other }
+    fn actions(&self, x: Coords, y: Coords, genome_id: GenomeId, operands: &[Gene],
+               perceptor: &dyn Perceptor, settings: &Settings) -> Vec<Box<dyn Action>>;
+    // Offset of the next gene, right after the operands unless it is a conditional
+    fn branch(&self, _x: Coords, _y: Coords, _genome_id: GenomeId, operands: &[Gene],
+              _perceptor: &dyn Perceptor, _settings: &Settings) -> GeneIndex {
+        1 + operands.len()
+    }
+}
+
+/// Decides what a cell does during its turn
+pub trait Brain {
+    fn think(&mut self, x: Coords, y: Coords, genome_id: GenomeId, perceptor: &dyn Perceptor, settings: &Settings) -> Vec<Box<dyn Action>>;
+
+    // New value of the gene in a mutated child genome, genes are instructions by default
+    fn mutate_gene(&self, gene: Gene, rng: &mut dyn RngCore) -> Gene {
+        BrainKind::Genes.mutate_gene(gene, rng)
+    }
 }
 
 pub trait Action {
@@ -166,17 +594,20 @@ pub struct UpdateHealthAction {
 pub struct ReproduceAction {
     pub x: Coords,
     pub y: Coords,
+    pub rotation: Gene,
 }
 
 pub struct AttackAction {
     pub x: Coords,
     pub y: Coords,
+    pub rotation: Gene,
     pub damage: HealthType,
 }
 
 pub struct MoveAction {
     pub x: Coords,
     pub y: Coords,
+    pub rotation: Gene,
 }
 
 pub struct RotateAction {
@@ -204,3 +635,34 @@ pub struct DecayAction {
     pub y: Coords,
     pub decay: HealthType,
 }
+
+pub struct AbsorbAction {
+    pub x: Coords,
+    pub y: Coords,
+    pub amount: HealthType,
+}
+
+pub struct EmitAction {
+    pub x: Coords,
+    pub y: Coords,
+    pub channel: usize,
+    pub amount: f64,
+}
+
+pub struct SetRegistersAction {
+    pub x: Coords,
+    pub y: Coords,
+    pub registers: Registers,
+}
+
+pub struct DefendAction {
+    pub x: Coords,
+    pub y: Coords,
+    pub duration: Tick,
+}
+
+pub struct SmellAction {
+    pub x: Coords,
+    pub y: Coords,
+    pub value: Gene, // rotation toward the strongest signal
+}
@@ -1,4 +1,6 @@
+use std::cmp;
 use std::fmt;
+use std::sync::Arc;
 use std::vec::Vec;
 
 use rand::{Rng};
@@ -14,25 +16,58 @@ impl World {
         let entities = (0..width * height)
             .map(|_| Entity::Nothing)
             .collect();
+        let region_map = vec![None; (width * height) as usize];
+        let nutrients = vec![0.0; (width * height) as usize];
+        let signals = vec![vec![0.0; (width * height) as usize]; SIGNAL_CHANNELS];
 
         World {
             width: width,
             height: height,
-            settings: settings,
+            regions: Vec::new(),
+            region_settings: Vec::new(),
+            region_map,
+            schedule: SettingsSchedule::new(),
+            events: EventQueue::new(),
+            light_flipped: false,
+            tick_no: 0,
             entities: entities,
+            nutrients,
+            signals,
             genomes: GenomeStorage::new(),
+            lineage: LineageStorage::new(),
+            species: SpeciesTracker::new(),
+            archive: GenomeArchive::new(),
+            gene_registry: GeneRegistry::new(),
+            observers: Vec::new(),
+            brain: settings.brain().build(),
+            settings: settings,
             cell_states: CellStateStorage::new()
         }
     }
 
+    /// Brain of another kind is built if the brain setting changes
     pub fn update_settings(&mut self, new_settings: Settings) {
+        if new_settings.brain() != self.settings.brain() {
+            self.brain = new_settings.brain().build();
+        }
         self.settings = new_settings;
+        self.settings_changed();
+    }
+
+    /// Custom brain thinks for the cells until the brain setting changes
+    pub fn set_brain(&mut self, brain: Box<dyn Brain>) {
+        self.brain = Some(brain);
     }
 
     pub fn get_settings(&self) -> Settings {
         self.settings.clone()
     }
 
+    /// Processor runs the gene instead of a goto from now on
+    pub fn register_gene(&mut self, handler: Box<dyn GeneHandler>) -> Result<(), String> {
+        self.gene_registry.register(handler)
+    }
+
     // TODO: synchronize?
     pub fn tick(&mut self, processor: &mut Processor) {
         //let start_time = PreciseTime::now();
@@ -40,18 +75,37 @@ impl World {
         // TODO move to processor?
         // TODO use linked list for performance
         let mut actions: Vec<Box<dyn Action>> = Vec::new();
+        for observer in self.observers.iter_mut() {
+            observer.tick_started(self.tick_no);
+        }
+        self.apply_schedule();
+        self.run_pending_events();
+        self.immigrate();
 
+        // Taken out for a while, the brain looks at the world while thinking
+        let mut brain = self.brain.take();
         for y in 0..self.height {
             for x in 0..self.width {
                 let idx = self.get_index(x, y);
                 let entity = self.entities[idx];
 
                 //println!("DEBUG: World.tick x: {:?} y: {:?} idx: {:?}", x, y, idx);
-                let mut action_batch = processor.process_entity(x, y, entity, self, &self.settings);
+                let mut action_batch = processor.process_entity(x, y, entity, self, self.settings_at(x, y),
+                                                                &mut brain);
                 actions.append(&mut action_batch);
             }
         }
+        self.brain = brain;
         processor.apply(&actions, self);
+        self.age_cells();
+        self.diffuse_nutrients();
+        self.spread_signals();
+        self.species.update(&self.genomes, &self.settings, self.tick_no);
+        self.archive.update(&self.genomes, self.tick_no);
+        for observer in self.observers.iter_mut() {
+            observer.tick_finished(self.tick_no);
+        }
+        self.tick_no += 1;
 
         // whatever you want to do
         //let end_time = PreciseTime::now();
@@ -59,13 +113,75 @@ impl World {
         //println!("DEBUG World.tick actions: {:?} time: {:?}", actions.len(), start_time.to(end_time));
     }
 
-    fn get_index(&self, x: Coords, y: Coords) -> usize {
+    // Cell previously living at this place dies of the cause
+    pub(crate) fn replace_entity(&mut self, x:Coords, y:Coords, entity: Entity, genome:Option<Genome>,
+                      initial_state: Option<CellState>, cause: DeathCause) {
+        let index = self.get_index(x, y);
+        //println!("set_entity x: {:?} y: {:?} index={:?}", x, y, index);
+        match self.entities[index] {
+            Entity::Cell(hash) => {
+                if let Some(genome) = self.genomes.get(hash) {
+                    self.archive.died(&genome.genes, cause, self.tick_no);
+                }
+                self.genomes.remove(hash); // TODO: should we?
+                self.cell_states.remove(hash);
+                self.lineage.died(hash, self.tick_no);
+                let (died_x, died_y) = self.normalize_coords(x, y);
+                for observer in self.observers.iter_mut() {
+                    observer.cell_died(died_x, died_y, hash, cause);
+                }
+            },
+            _ => {}
+        }
+        match entity {
+            Entity::Cell(hash) => {
+                let mut genome = genome.unwrap();
+                genome.birth_tick = self.tick_no;
+                let parent_genes = genome.parent_id()
+                    .and_then(|parent_id| self.genomes.get(parent_id))
+                    .map(|parent| Arc::clone(&parent.genes));
+                let category = GenomeDesc::build_with(&genome, &self.gene_registry).icon();
+                self.lineage.born_as(&genome, category);
+                self.archive.born(&genome, parent_genes.as_deref(), self.tick_no);
+                let (born_x, born_y) = self.normalize_coords(x, y);
+                for observer in self.observers.iter_mut() {
+                    observer.cell_born(born_x, born_y, &genome);
+                }
+                self.genomes.put_with(genome, &self.gene_registry);
+                self.cell_states.put(hash, initial_state.unwrap());
+            },
+            _ => {}
+        }
+        self.entities[index] = entity;
+    }
+
+    /// Coordinates behind the edge of the world are None, wrapped ones are left as is
+    pub fn resolve(&self, x: Coords, y: Coords) -> Option<(Coords, Coords)> {
+        let inside_x = x >= 0 && x < self.width;
+        let inside_y = y >= 0 && y < self.height;
+
+        let inside = match self.settings.topology() {
+            Topology::Torus => true,
+            Topology::Box => inside_x && inside_y,
+            Topology::Cylinder => inside_y,
+        };
+
+        if inside { Some((x, y)) } else { None }
+    }
+
+    // Only resolved coordinates should get here, the rest is wrapped
+    pub(crate) fn get_index(&self, x: Coords, y: Coords) -> usize {
         let x2 = World::normalize(x, self.width);
         let y2 = World::normalize(y, self.height);
 
         (y2 * self.width + x2) as usize
     }
 
+    /// Wrapped coordinates brought back inside the world
+    pub(crate) fn normalize_coords(&self, x: Coords, y: Coords) -> (Coords, Coords) {
+        (World::normalize(x, self.width), World::normalize(y, self.height))
+    }
+
     fn normalize(coord:Coords, dimension: Coords) -> Coords {
         let remainder = coord % dimension;
         if coord < 0 {
@@ -78,22 +194,13 @@ impl World {
 
 impl fmt::Display for World {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fn icon_for(desc: &GenomeDesc) -> char {
-            match desc {
-                x if x.reproduces > x.attacks + x.photosynthesis => '*',
-                x if x.attacks > x.photosynthesis + x.defiles  => 'x',
-                x if x.photosynthesis > x.attacks + x.defiles => 'o',
-                x if x.defiles > x.attacks + x.photosynthesis => '@',
-                _ => '.'
-            }
-        }
-
         for line in self.entities.as_slice().chunks(self.width as usize) {
             for &entity in line {
                 let symbol = match entity {
                     Entity::Nothing => ' ',
-                    Entity::Cell(genome_id) => icon_for(&self.genomes.describe(genome_id).unwrap()),
+                    Entity::Cell(genome_id) => self.genomes.describe(genome_id).unwrap().icon(),
                     Entity::Corpse(_) => '+',
+                    Entity::Wall => '#',
                 };
                 write!(f, "{}", symbol)?;
             }
@@ -118,7 +225,7 @@ impl Affector for World {
     }
 
     fn set_cell_ext(&mut self, x:Coords, y:Coords, genome:Genome, direction: Direction) {
-        let initial_health = self.settings.initial_cell_health;
+        let initial_health = self.settings_at(x, y).initial_cell_health;
         self.set_entity(
             x,
             y,
@@ -128,18 +235,23 @@ impl Affector for World {
         );
     }
 
-    fn move_cell(&mut self, x:Coords, y:Coords) {
+    fn move_cell_towards(&mut self, x:Coords, y:Coords, rotation: Gene) {
         let old_index = self.get_index(x, y);
 
         match self.entities[old_index] {
             Entity::Cell(genome_id) => {
-                if let Some((new_x, new_y)) = self.looking_at(x, y) {
+                if let Some((new_x, new_y)) = self.looking_towards(x, y, rotation) {
                     let new_index = self.get_index(new_x, new_y);
 
                     match self.entities[new_index] {
                         Entity::Nothing => {
                             self.entities[new_index] = Entity::Cell(genome_id);
                             self.entities[old_index] = Entity::Nothing;
+                            let from = self.normalize_coords(x, y);
+                            let to = self.normalize_coords(new_x, new_y);
+                            for observer in self.observers.iter_mut() {
+                                observer.cell_moved(genome_id, from, to);
+                            }
                         }
                         _ => {}
                     }
@@ -152,8 +264,9 @@ impl Affector for World {
     fn rotate_cell(&mut self, x:Coords, y:Coords, value: Gene) {
         match self.entities[self.get_index(x, y)] {
             Entity::Cell(genome_id) => {
+                let neighbourhood = self.settings.neighbourhood();
                 let mut cell_state = self.cell_states.get_mut(genome_id);
-                cell_state.direction = cell_state.direction.rotate(value);
+                cell_state.direction = neighbourhood.rotate(cell_state.direction, value);
 //                println!("DEBUG: world.rotate_cell rotated x:{:?}, y:{:?}, value:{:?}, new direction:{:?}",
 //                    x, y, value, cell_state.direction);
             }
@@ -162,23 +275,7 @@ impl Affector for World {
     }
 
     fn set_entity(&mut self, x:Coords, y:Coords, entity: Entity, genome:Option<Genome>, initial_state: Option<CellState>) {
-        let index = self.get_index(x, y);
-        //println!("set_entity x: {:?} y: {:?} index={:?}", x, y, index);
-        match self.entities[index] {
-            Entity::Cell(hash) => {
-                self.genomes.remove(hash); // TODO: should we?
-                self.cell_states.remove(hash);
-            },
-            _ => {}
-        }
-        match entity {
-            Entity::Cell(hash) => {
-                self.genomes.put(genome.unwrap());
-                self.cell_states.put(hash, initial_state.unwrap());
-            },
-            _ => {}
-        }
-        self.entities[index] = entity;
+        self.replace_entity(x, y, entity, genome, initial_state, DeathCause::Killed);
     }
 
     /// Returns positive amount of health bitten from target
@@ -190,6 +287,10 @@ impl Affector for World {
     /// * `health_delta` - will be added to cell's health
 
     fn update_health(&mut self, x:Coords, y:Coords, health_delta: HealthType) -> HealthType {
+        self.update_health_ext(x, y, health_delta, DeathCause::Starvation)
+    }
+
+    fn update_health_ext(&mut self, x:Coords, y:Coords, health_delta: HealthType, cause: DeathCause) -> HealthType {
         let old_health;
         let new_health;
 
@@ -208,11 +309,15 @@ impl Affector for World {
 //                             x, y, genome_id, health_delta, state.health);
                 }
 
+                let max_health = self.settings_at(x, y).max_health();
                 if new_health < 0 {
                     result = old_health;
-                    let corpse_health = self.settings.corpse_initial();
-                    self.set_entity(x, y, Entity::Corpse(corpse_health), None, None);
+                    let corpse_health = self.settings_at(x, y).corpse_initial();
+                    self.replace_entity(x, y, Entity::Corpse(corpse_health), None, None, cause);
 //                    println!("DEBUG: Affector.update_health KILLED x={:?} y={:?}", x, y);
+                } else if max_health > 0 && new_health > max_health {
+                    self.cell_states.get_mut(genome_id).health = max_health;
+                    self.release_overflow(x, y, new_health - max_health);
                 }
             },
             _ => {}
@@ -243,13 +348,18 @@ impl Affector for World {
     }
 
     fn punish_for_action(&mut self, x:Coords, y:Coords, gene: Gene) {
+        let settings = self.settings_at(x, y);
         let value = match gene {
-            SENSE => self.settings.sense_cost(),
-            TURN => self.settings.turn_cost(),
-            MOVE => self.settings.move_cost(),
-            ATTACK => self.settings.attack_cost(),
-            REPRODUCE => self.settings.reproduce_cost(),
-            DEFILE => self.settings.defile_cost(),
+            SENSE => settings.sense_cost(),
+            TURN => settings.turn_cost(),
+            MOVE => settings.move_cost(),
+            ATTACK => settings.attack_cost(),
+            REPRODUCE => settings.reproduce_cost(),
+            DEFILE => settings.defile_cost(),
+            ABSORB => settings.absorb_cost(),
+            EMIT => settings.emit_cost(),
+            SMELL => settings.smell_cost(),
+            DEFEND => settings.defend_cost(),
             _ => 0
         };
         // println!("DEBUG: Affector.punish_for_action x={:?} y={:?} gene={:?}", x, y, gene);
@@ -257,14 +367,22 @@ impl Affector for World {
         self.update_health(x, y, value);
     }
 
-    fn attack(&mut self, x:Coords, y:Coords, damage: HealthType) {
+    fn attack_towards(&mut self, x:Coords, y:Coords, rotation: Gene, damage: HealthType) {
         match self.entities[self.get_index(x, y)] {
             Entity::Cell(_) => {
-                if let Some((new_x, new_y)) = self.looking_at(x, y) {
+                if let Some((new_x, new_y)) = self.looking_towards(x, y, rotation) {
 //                    println!("DEBUG: Affector.attack x: {:?} y: {:?} new_x: {:?}, new_y: {:?} damage: {:?}",
 //                             x, y, new_x, new_y, damage);
 
-                    let health_eaten = self.update_health(new_x, new_y, -damage);
+                    let damage = self.damage_after_defence(new_x, new_y, damage);
+                    if let Entity::Cell(_) = self.entities[self.get_index(new_x, new_y)] {
+                        let attacker = self.normalize_coords(x, y);
+                        let target = self.normalize_coords(new_x, new_y);
+                        for observer in self.observers.iter_mut() {
+                            observer.attack_landed(attacker, target, damage);
+                        }
+                    }
+                    let health_eaten = self.update_health_ext(new_x, new_y, -damage, DeathCause::Attack);
                     self.update_health(x, y, health_eaten);
                 }
             },
@@ -274,25 +392,101 @@ impl Affector for World {
         }
     }
 
-    fn reproduce(&mut self, x:Coords, y:Coords) {
+    fn reproduce_towards(&mut self, x:Coords, y:Coords, rotation: Gene) {
+        self.spawn_child(x, y, rotation);
+    }
+
+    fn decay(&mut self, x:Coords, y:Coords, decay: HealthType) {
+        if let Entity::Corpse(remains) = self.entities[self.get_index(x, y)] {
+            // Whatever has decayed goes to the soil
+            let released = if remains + decay > 0 {
+                self.set_corpse(x, y, remains + decay);
+                -decay
+            } else {
+                self.set_nothing(x, y);
+                remains
+            };
+            self.add_nutrients(x, y, cmp::max(released, 0) as f64);
+            let (corpse_x, corpse_y) = self.normalize_coords(x, y);
+            for observer in self.observers.iter_mut() {
+                observer.corpse_decayed(corpse_x, corpse_y, remains - released);
+            }
+        }
+    }
+
+    fn emit(&mut self, x:Coords, y:Coords, channel: usize, amount: f64) {
+        if let Entity::Cell(_) = self.entities[self.get_index(x, y)] {
+            self.add_signal(x, y, channel, amount);
+        }
+    }
+
+    fn defend(&mut self, x:Coords, y:Coords, duration: Tick) {
+        if let Entity::Cell(genome_id) = self.entities[self.get_index(x, y)] {
+            let state = self.cell_states.get_mut(genome_id);
+            state.defended_for = cmp::max(state.defended_for, duration);
+        }
+    }
+
+    fn set_registers(&mut self, x:Coords, y:Coords, registers: Registers) {
+        if let Entity::Cell(genome_id) = self.entities[self.get_index(x, y)] {
+            self.cell_states.get_mut(genome_id).registers = registers;
+        }
+    }
+
+    fn absorb(&mut self, x:Coords, y:Coords, amount: HealthType) {
+        if let Entity::Cell(_) = self.entities[self.get_index(x, y)] {
+            let absorbed = self.take_nutrients(x, y, amount);
+            self.update_health(x, y, absorbed);
+        }
+    }
+
+    fn build_child_genome_for(&self, parent_genome_id: GenomeId) -> Option<Genome> {
+        self.build_child_genome_with(parent_genome_id, &self.settings)
+    }
+}
+
+impl World {
+    // Hardened cell blocks part of the damage, so the attacker gets less food too
+    fn damage_after_defence(&self, x: Coords, y: Coords, damage: HealthType) -> HealthType {
+        match self.get_state_by_pos(x, y) {
+            Some(state) if state.defended_for > 0 => {
+                let reduction = self.settings_at(x, y).defend_reduction();
+                (damage as f64 * (1.0 - reduction)).round() as HealthType
+            },
+            _ => damage
+        }
+    }
+
+    // Position of the child, None if there was no room for it
+    fn spawn_child(&mut self, x:Coords, y:Coords, rotation: Gene) -> Option<(Coords, Coords)> {
+        let settings = self.settings_at(x, y);
         let new_genome = match self.get_entity(x, y) {
-            Entity::Cell(genome_id) => self.build_child_genome_for(*genome_id),
+            Entity::Cell(genome_id) => self.build_child_genome_with(*genome_id, settings),
             _ => None
         };
 
         match new_genome {
             Some(new_genome) => {
-                if let Some((new_x, new_y)) = self.looking_at(x, y) {
+                if let Some((new_x, new_y)) = self.looking_towards(x, y, rotation) {
                     // Can reproduce only on corpse or empty space
                     match self.get_entity(new_x, new_y) {
-                        Entity::Cell(_) => {},
+                        Entity::Cell(_) | Entity::Wall => {},
                         _ => {
 //                            println!("DEBUG: Affector.reproduce x:{:?}, y:{:?} looking_at: ({:?}, {:?})",
 //                                     x, y, new_x, new_y);
 
                             let mut rng = rand::thread_rng();
-                            let direction = Direction::by_value(rng.gen_range(0, 8));
+                            let neighbourhood = self.settings.neighbourhood();
+                            let direction = neighbourhood.by_value(rng.gen_range(0, neighbourhood.size()));
+                            let registers = self.get_state_by_pos(x, y).map(|state| state.registers);
                             self.set_cell_ext(new_x, new_y, new_genome, direction);
+
+                            match registers {
+                                Some(registers) if self.settings.inherit_registers() =>
+                                    self.set_registers(new_x, new_y, registers),
+                                _ => {}
+                            }
+                            return Some((new_x, new_y));
                         }
                     }
                 }
@@ -301,33 +495,72 @@ impl Affector for World {
 //                println!("DEBUG: Affector.reproduce new genome is shit")
             }
         }
-
+        None
     }
 
-    fn decay(&mut self, x:Coords, y:Coords, decay: HealthType) {
-        if let Entity::Corpse(remains) = self.entities[self.get_index(x, y)] {
-            if remains > decay {
-                self.set_corpse(x, y, remains + decay);
-            } else {
-                self.set_nothing(x, y);
+    fn release_overflow(&mut self, x: Coords, y: Coords, excess: HealthType) {
+        let settings = self.settings_at(x, y);
+        let max_health = settings.max_health();
+
+        match settings.health_overflow() {
+            HealthOverflow::Lost => {},
+            // Child is paid for by the excess only, it is lost if there is no room
+            HealthOverflow::Reproduce => {
+                if let Some((child_x, child_y)) = self.spawn_child(x, y, 0) {
+                    if let Entity::Cell(genome_id) = *self.get_entity(child_x, child_y) {
+                        self.cell_states.get_mut(genome_id).health = cmp::min(excess, max_health);
+                    }
+                }
+            },
+            HealthOverflow::Share => {
+                // Small worlds could wrap the same neighbour, or the cell itself, several times
+                let neighbourhood = settings.neighbourhood();
+                let mut neighbours: Vec<GenomeId> = neighbourhood
+                    .directions()
+                    .iter()
+                    .filter_map(|direction| {
+                        let (dx, dy) = neighbourhood.shift(*direction, y);
+                        self.resolve(x + dx, y + dy)
+                    })
+                    .filter_map(|(new_x, new_y)| match self.get_entity(new_x, new_y) {
+                        Entity::Cell(genome_id) => Some(*genome_id),
+                        _ => None
+                    })
+                    .filter(|genome_id| Entity::Cell(*genome_id) != *self.get_entity(x, y))
+                    .collect();
+                neighbours.sort();
+                neighbours.dedup();
+                if neighbours.is_empty() {
+                    return;
+                }
+
+                // Neighbours are never pushed over the maximum, so this never cascades
+                let share = excess / neighbours.len() as HealthType;
+                for genome_id in neighbours {
+                    let state = self.cell_states.get_mut(genome_id);
+                    state.health = cmp::min(state.health + share, cmp::max(state.health, max_health));
+                }
             }
         }
     }
 
-    fn build_child_genome_for(&self, parent_genome_id: GenomeId) -> Option<Genome> {
+    // Mutated gene depends on how the brain reads genes
+    fn build_child_genome_with(&self, parent_genome_id: GenomeId, settings: &Settings) -> Option<Genome> {
         let mut rng = rand::thread_rng();
-        let probability = self.settings.mutation_probability();
 
-        let probability = rng.gen_bool(probability);
+        let probability = rng.gen_bool(self.mutation_probability(settings));
         let index = rng.gen_range(0, GENOME_LENGTH);
-        let new_gene = rng.gen_range(0, GENE_COUNT);
 
         self.genomes
             .get(parent_genome_id)
             .map(|genome| {
-                let mut result = genome.clone();
+                let mut result = genome.new_child();
                 if probability {
-                    result.mutate(index, new_gene);
+                    let new_gene = match &self.brain {
+                        Some(brain) => brain.mutate_gene(genome.genes[index], &mut rng),
+                        None => BrainKind::Genes.mutate_gene(genome.genes[index], &mut rng),
+                    };
+                    result.apply_mutation(index, new_gene);
                 }
                 result
             })
@@ -358,16 +591,34 @@ impl Perceptor for World {
         self.genomes.get(hash)
     }
 
-    fn looking_at(&self, x: i32, y: i32) ->Option<(Coords, Coords)> {
+    fn looking_towards(&self, x: Coords, y: Coords, rotation: Gene) -> Option<(Coords, Coords)> {
         match self.get_entity(x, y) {
             Entity::Cell(genome_id) => {
                 let cell_state = self.cell_states.get(*genome_id);
-                let (dx, dy) = cell_state.direction.shift();
-                Some((x + dx, y + dy))
+                let neighbourhood = self.settings.neighbourhood();
+                let direction = neighbourhood.rotate(cell_state.direction, rotation);
+                let (dx, dy) = neighbourhood.shift(direction, y);
+                self.resolve(x + dx, y + dy)
             },
             _ => None
         }
     }
+
+    fn resolve(&self, x: Coords, y: Coords) -> Option<(Coords, Coords)> {
+        World::resolve(self, x, y)
+    }
+
+    fn light_at(&self, x: Coords, y: Coords) -> f64 {
+        self.light(x, y)
+    }
+
+    fn gene_registry(&self) -> &GeneRegistry {
+        &self.gene_registry
+    }
+
+    fn signal_at(&self, x: Coords, y: Coords, channel: usize) -> f64 {
+        self.signal(x, y, channel)
+    }
 }
 
 #[cfg(test)]
@@ -407,7 +658,7 @@ mod tests {
             0,
             Entity::Cell(plant.id()),
             Some(plant),
-            Some(CellState { health: 10, direction: Direction::North })
+            Some(CellState::new(10, Direction::North))
         );
 
         assert_eq!(Some((0, -1)), world.looking_at(0,0));
@@ -430,6 +681,62 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_looking_at_neighbourhoods() {
+        let settings = SettingsBuilder::prod().with_neighbourhood(Neighbourhood::Hex).build();
+        let mut world = World::new(4, 4, settings);
+        world.set_cell_ext(1, 1, Genome::new_plant(), Direction::NorthEast);
+
+        // Odd row is shifted to the east
+        assert_eq!(Some((2, 0)), world.looking_at(1, 1));
+        world.rotate_cell(1, 1, 4);
+        assert_eq!(Direction::West, world.get_state_by_pos(1, 1).unwrap().direction);
+        assert_eq!(Some((1, 2)), world.looking_towards(1, 1, 5));
+
+        let settings = SettingsBuilder::prod().with_neighbourhood(Neighbourhood::VonNeumann).build();
+        let mut world = World::new(4, 4, settings);
+        world.set_cell_ext(1, 1, Genome::new_plant(), Direction::North);
+        world.rotate_cell(1, 1, 3);
+        assert_eq!(Some((0, 1)), world.looking_at(1, 1));
+    }
+
+    #[test]
+    fn test_registers_at_reproduction() {
+        for inherit in [false, true].iter() {
+            let settings = SettingsBuilder::prod()
+                .with_mutation_probability(0.0)
+                .with_inherit_registers(*inherit)
+                .build();
+            let mut world = World::new(2, 1, settings);
+            world.set_cell_ext(0, 0, Genome::new_plant(), Direction::East);
+            world.set_registers(0, 0, [1, 2, 3, 4]);
+
+            world.reproduce(0, 0);
+            let expected = if *inherit { [1, 2, 3, 4] } else { [0; REGISTERS] };
+            assert_eq!(expected, world.get_state_by_pos(1, 0).unwrap().registers);
+        }
+    }
+
+    #[test]
+    fn test_looking_at_walls() {
+        let settings = SettingsBuilder::prod().with_topology(Topology::Box).build();
+        let mut world = World::new(2, 2, settings);
+        world.set_cell_ext(0, 0, Genome::new_plant(), Direction::West);
+        world.set_cell_ext(1, 1, Genome::new_plant(), Direction::East);
+
+        assert_eq!(None, world.looking_at(0, 0));
+        assert_eq!(None, world.looking_at(1, 1));
+        world.rotate_cell(0, 0, 2);
+        assert_eq!(None, world.looking_at(0, 0));
+        world.rotate_cell(0, 0, 2);
+        assert_eq!(Some((1, 0)), world.looking_at(0, 0));
+
+        world.update_settings(SettingsBuilder::prod().with_topology(Topology::Cylinder).build());
+        assert_eq!(Some((2, 1)), world.looking_at(1, 1));
+        world.rotate_cell(1, 1, 2);
+        assert_eq!(None, world.looking_at(1, 1));
+    }
+
     #[test]
     fn test_update_health_addition() {
         let settings = Settings::prod();
@@ -511,7 +818,7 @@ mod tests {
             0,
             Entity::Cell(genome_id),
             Some(plant),
-            Some(CellState { health: initial_cell_health, direction: Direction::West })
+            Some(CellState::new(initial_cell_health, Direction::West))
         );
 
         world.tick(&mut Processor::new());
@@ -618,6 +925,147 @@ mod tests {
 
     }
 
+    #[test]
+    fn integration_test_lineage_is_recorded() {
+        let mut world = World::new(3, 1, SettingsBuilder::zero());
+        let yeast = Genome::new_yeast();
+        let parent_id = yeast.id();
+        world.set_cell_ext(1, 0, yeast, Direction::West);
+
+        world.tick(&mut Processor::new());
+
+        let child_id = match world.get_entity(0, 0) {
+            Entity::Cell(child_id) => *child_id,
+            _ => panic!("New cell was not reproduced!")
+        };
+
+        let record = world.lineage.get(child_id).unwrap();
+        assert_eq!(Some(parent_id), record.parent_id);
+        assert_eq!(0, record.birth_tick);
+        assert_eq!(Some(parent_id), world.get_genome(child_id).unwrap().parent_id());
+        assert_eq!(vec![child_id], world.lineage.descendants(parent_id));
+
+        // Parent is dead, but stays in lineage as an ancestor of a living cell
+        world.set_nothing(1, 0);
+        assert!(!world.lineage.is_alive(parent_id));
+        assert_eq!(vec![parent_id], world.lineage.ancestors(child_id));
+    }
+
+    #[test]
+    fn integration_test_archive_keeps_dead_genotypes() {
+        let mut world = World::new(2, 1, SettingsBuilder::zero());
+        let plant = Genome::new_plant();
+        let genes = *plant.genes;
+        world.set_cell_ext(0, 0, plant, Direction::East);
+        world.set_cell_ext(1, 0, Genome::new_predator(), Direction::West);
+
+        world.attack(1, 0, 100);
+        world.tick(&mut Processor::new());
+
+        let record = world.archive.get(&genes).unwrap();
+        assert_eq!(0, record.population);
+        assert_eq!(Some(&1), record.deaths.get(&DeathCause::Attack));
+        assert_eq!(PHOTOSYNTHESIS, record.genes[0]);
+    }
+
+    #[test]
+    fn test_health_cap() {
+        let settings = SettingsBuilder::prod().with_max_health(15).build();
+        let mut world = World::new(3, 1, settings);
+
+        world.set_cell_ext(0, 0, Genome::new_plant(), Direction::East);
+        world.update_health(0, 0, 100);
+        assert_eq!(15, world.get_state_by_pos(0, 0).unwrap().health);
+
+        // Victim can't lose more than it has, attacker can't get over the cap
+        world.set_cell_ext(1, 0, Genome::new_plant(), Direction::West);
+        world.attack(0, 0, 100);
+        assert_eq!(15, world.get_state_by_pos(0, 0).unwrap().health);
+    }
+
+    #[test]
+    fn test_health_overflow() {
+        let settings = SettingsBuilder::prod()
+            .with_max_health(15)
+            .with_mutation_probability(0.0)
+            .with_health_overflow(HealthOverflow::Reproduce)
+            .build();
+        let mut world = World::new(3, 1, settings);
+        world.set_cell_ext(1, 0, Genome::new_plant(), Direction::East);
+        world.update_health(1, 0, 10);
+        match world.get_entity(2, 0) {
+            Entity::Cell(_) => {},
+            _ => panic!("Overflow should make a child")
+        }
+        // 10 + 10 is split between the parent at the cap and the child
+        assert_eq!(15, world.get_state_by_pos(1, 0).unwrap().health);
+        assert_eq!(5, world.get_state_by_pos(2, 0).unwrap().health);
+
+        let settings = SettingsBuilder::prod()
+            .with_max_health(15)
+            .with_health_overflow(HealthOverflow::Share)
+            .build();
+        let mut world = World::new(3, 1, settings);
+        world.set_cell(0, 0, Genome::new_plant());
+        world.set_cell(1, 0, Genome::new_plant());
+        world.set_cell(2, 0, Genome::new_plant());
+        // 10 over the cap, both neighbours could take only 5
+        world.update_health(1, 0, 15);
+        assert_eq!(15, world.get_state_by_pos(0, 0).unwrap().health);
+        assert_eq!(15, world.get_state_by_pos(1, 0).unwrap().health);
+        assert_eq!(15, world.get_state_by_pos(2, 0).unwrap().health);
+    }
+
+    #[test]
+    fn test_health_overflow_conserves_energy() {
+        let settings = SettingsBuilder::prod()
+            .with_topology(Topology::Box)
+            .with_max_health(15)
+            .with_mutation_probability(0.0)
+            .with_health_overflow(HealthOverflow::Reproduce)
+            .build();
+        let mut world = World::new(4, 1, settings);
+        world.set_cell_ext(0, 0, Genome::new_plant(), Direction::East);
+        let total_health = |world: &World| -> HealthType {
+            world.cell_states.states.values().map(|state| state.health).sum()
+        };
+
+        // One point over the cap makes a child with one point
+        world.update_health(0, 0, 6);
+        assert_eq!(16, total_health(&world));
+        assert_eq!(1, world.get_state_by_pos(1, 0).unwrap().health);
+
+        // No room for another child, the excess is lost, never created
+        let mut added = 16;
+        for _ in 0..5 {
+            world.update_health(0, 0, 7);
+            added += 7;
+            assert!(total_health(&world) <= added);
+        }
+        assert_eq!(2, world.cell_states.states.len());
+    }
+
+    #[test]
+    fn integration_test_walls_stop_everything() {
+        let mut world = World::new(2, 1, SettingsBuilder::zero());
+        let mut genome = Genome::new_yeast();
+        genome.mutate(0, MOVE);
+        genome.mutate(1, ATTACK);
+        world.set_cell_ext(0, 0, genome, Direction::East);
+        world.set_entity(1, 0, Entity::Wall, None, None);
+
+        for _ in 0..3 {
+            world.tick(&mut Processor::new());
+        }
+
+        assert_eq!(Entity::Wall, *world.get_entity(1, 0));
+        match world.get_entity(0, 0) {
+            Entity::Cell(_) => {},
+            _ => panic!("Cell should stay where it was")
+        }
+        assert_eq!("*#\n", format!("{}", world));
+    }
+
     #[test]
     fn integration_test_order_of_execution_parent_killed() {
         let mut world = World::new(3, 1, SettingsBuilder::zero());